@@ -1,4 +1,4 @@
-use bonuses::{BonusType, valid_bonuses};
+use bonuses::{BonusType, announcement_consistent};
 use cards::CardSuit;
 use player::{PlayerTurn, Player, PlayerId};
 
@@ -96,7 +96,7 @@ impl Announcements {
 
 // Check if the announced bonuses for the player are valid.
 fn check_bonuses_valid(player: &Player, bonuses: &HashSet<BonusType>, king: Option<CardSuit>) -> bool {
-    bonuses.is_subset(&valid_bonuses(player, king))
+    announcement_consistent(bonuses, player.hand(), king)
 }
 
 #[cfg(test)]
@@ -1,4 +1,6 @@
-use contracts::{Contract, STANDARD_THREE};
+use cards::{Hand, TarockCard, Tarock15};
+use contracts::{Contract, CONTRACTS, STANDARD_THREE, Klop, Standard, Solo, SoloWithout,
+    Beggar, Valat, Three, Two, One, beggar, valat};
 use player::{PlayerId, PlayerTurn};
 
 #[deriving(Eq, PartialEq, Show)]
@@ -41,6 +43,8 @@ pub struct Bid {
     player: PlayerId,
     player_priority: uint,
     contract: Contract,
+    // True if the player intends to double the contract.
+    kontra: bool,
 }
 
 impl Bid {
@@ -50,9 +54,20 @@ impl Bid {
             player: player,
             player_priority: priority,
             contract: contract,
+            kontra: false,
         }
     }
 
+    // Returns the same bid with the kontra flag set.
+    pub fn with_kontra(self) -> Bid {
+        Bid { kontra: true, ..self }
+    }
+
+    // Returns true if the bid carries a kontra.
+    pub fn kontra(&self) -> bool {
+        self.kontra
+    }
+
     // Return the contract that was bid by the player.
     pub fn contract(&self) -> Contract {
         self.contract
@@ -64,8 +79,84 @@ impl Bid {
     }
 }
 
+// The outcome of a finished bidding.
+#[deriving(Eq, PartialEq, Show)]
+pub struct BiddingResult {
+    declarer: PlayerId,
+    contract: Contract,
+    kontra: bool,
+}
+
+impl BiddingResult {
+    // Returns the player that won the bidding.
+    pub fn declarer(&self) -> PlayerId {
+        self.declarer
+    }
+
+    // Returns the contract that will be played.
+    pub fn contract(&self) -> Contract {
+        self.contract
+    }
+
+    // Returns true if the winning bid carried a kontra.
+    pub fn kontra(&self) -> bool {
+        self.kontra
+    }
+}
+
+// A rough measure of the strength of a hand used for bidding.
+#[deriving(Clone, Eq, PartialEq, Show)]
+pub struct HandStrength {
+    // Number of tarocks in the hand.
+    pub tarocks: uint,
+    // Number of tarocks from XV up, including the skis.
+    pub high_tarocks: uint,
+    // Number of kings in the hand.
+    pub kings: uint,
+}
+
+impl HandStrength {
+    // Measures the strength of the hand.
+    pub fn new(hand: &Hand) -> HandStrength {
+        HandStrength {
+            tarocks: hand.cards().filter(|card| card.is_tarock()).count(),
+            high_tarocks: hand.cards().filter(|card| match **card {
+                TarockCard(tarock) => tarock.index() >= Tarock15.index(),
+                _ => false,
+            }).count(),
+            kings: hand.cards().filter(|card| card.is_king()).count(),
+        }
+    }
+}
+
+// Returns true if the hand is strong enough to bid the contract.
+// Positive contracts need more tarocks the higher they are, beggar contracts
+// need a hand without high cards.
+pub fn is_reasonable_bid(contract: &Contract, strength: &HandStrength) -> bool {
+    match *contract {
+        Klop | Standard(Three) => true,
+        Standard(Two) => strength.tarocks >= 4,
+        Standard(One) => strength.tarocks >= 5,
+        Solo(Three) => strength.tarocks >= 6,
+        Solo(Two) => strength.tarocks >= 7,
+        Solo(One) => strength.tarocks >= 8 && strength.high_tarocks >= 3,
+        SoloWithout => strength.tarocks >= 9 && strength.high_tarocks >= 4,
+        Beggar(beggar::Normal) => strength.high_tarocks == 0 && strength.kings == 0,
+        Beggar(beggar::Open) => strength.tarocks <= 1 && strength.high_tarocks == 0 && strength.kings == 0,
+        Valat(valat::Color) => strength.tarocks <= 1 && strength.kings >= 3,
+        Valat(valat::Normal) => strength.tarocks >= 10 && strength.high_tarocks >= 5,
+    }
+}
+
+// A recorded bidding action of a player.
+#[deriving(Clone, Eq, PartialEq, Show, Encodable, Decodable)]
+pub enum BidAction {
+    PlayerBid(PlayerId, Contract),
+    PlayerPass(PlayerId),
+}
+
 // A 4-player bidding helper.
-struct Bidder {
+pub struct Bidder {
     forehand: PlayerId,
     done: bool,
     highest: Bid,
@@ -102,29 +193,44 @@ impl Bidder {
         &self.highest
     }
 
+    // Bids a contract for a player together with the intent to double it.
+    pub fn bid_with_kontra(&mut self, player: &PlayerId, contract: Contract) -> Result<Success, BidError> {
+        let result = try!(self.bid(player, contract));
+        self.highest = self.highest.with_kontra();
+        Ok(result)
+    }
+
+    // Returns the result of the bidding or `None` if the bidding is not done yet.
+    pub fn finalize(&self) -> Option<BiddingResult> {
+        self.winner().map(|bid| BiddingResult {
+            declarer: bid.player(),
+            contract: bid.contract(),
+            kontra: bid.kontra(),
+        })
+    }
+
+    // Returns true if the bidding is done and no player bid above the default
+    // contract, so the forehand player plays the default contract.
+    pub fn won_by_default(&self) -> bool {
+        self.is_done() && self.highest.player() == self.forehand && self.highest.contract() == DEFAULT_CONTRACT
+    }
+
     // Returns true if forehand player is bidding and the only bid is the default.
     fn has_no_bets(&self, player: &PlayerId) -> bool {
         &self.forehand == player && self.highest.contract() == DEFAULT_CONTRACT
     }
 
-    fn next_player(&mut self, f: |&mut PlayerTurn| -> PlayerId) -> Success {
-        if self.turn.current_players() == 1 {
-            // Now that the last remaining player bidding has bid we are done.
-            self.done = true;
-            Last
-        } else {
-            Next(f(&mut self.turn))
-        }
-    }
-}
-
-impl Bidding for Bidder {
-    fn current_player(&self) -> &PlayerId {
-        self.turn.current()
+    // Returns true if the bidding is stuck: it is not finished but the current
+    // player can neither bid nor pass.
+    pub fn is_stalemate(&self) -> bool {
+        let player = self.turn.current();
+        !self.is_done() && self.check_pass(player).is_err() &&
+            !CONTRACTS.iter().any(|contract| self.check_bid(player, *contract).is_ok())
     }
 
-    fn bid(&mut self, player: &PlayerId, contract: Contract) -> Result<Success, BidError> {
-        let bid = Bid::new(*self.turn.current(), player_priority(&self.turn, player), contract);
+    // Checks if the player is allowed to bid the contract.
+    fn check_bid(&self, player: &PlayerId, contract: Contract) -> Result<(), BidError> {
+        let bid = Bid::new(*player, player_priority(&self.turn, player), contract);
         if self.is_done() {
             Err(Done)
         } else if self.turn.current() != player {
@@ -136,12 +242,12 @@ impl Bidding for Bidder {
         } else if !is_bid_valid(&self.highest, &bid){
             Err(ContractTooLow)
         } else {
-            self.highest = bid;
-            Ok(self.next_player(|turn| *turn.next()))
+            Ok(())
         }
     }
 
-    fn pass(&mut self, player: &PlayerId) -> Result<Success, BidError> {
+    // Checks if the player is allowed to pass.
+    fn check_pass(&self, player: &PlayerId) -> Result<(), BidError> {
         if self.is_done() {
             Err(Done)
         } else if self.turn.current() != player {
@@ -151,13 +257,40 @@ impl Bidding for Bidder {
             // player bidding did not bid yet.
             Err(MustBid)
         } else {
-            Ok(self.next_player(|turn| {
-                // Player that passes the bid cannot rejoin the bidding again.
-                *turn.remove()
-            }))
+            Ok(())
         }
     }
 
+    fn next_player(&mut self, f: |&mut PlayerTurn| -> PlayerId) -> Success {
+        if self.turn.current_players() == 1 {
+            // Now that the last remaining player bidding has bid we are done.
+            self.done = true;
+            Last
+        } else {
+            Next(f(&mut self.turn))
+        }
+    }
+}
+
+impl Bidding for Bidder {
+    fn current_player(&self) -> &PlayerId {
+        self.turn.current()
+    }
+
+    fn bid(&mut self, player: &PlayerId, contract: Contract) -> Result<Success, BidError> {
+        try!(self.check_bid(player, contract));
+        self.highest = Bid::new(*player, player_priority(&self.turn, player), contract);
+        Ok(self.next_player(|turn| *turn.next()))
+    }
+
+    fn pass(&mut self, player: &PlayerId) -> Result<Success, BidError> {
+        try!(self.check_pass(player));
+        Ok(self.next_player(|turn| {
+            // Player that passes the bid cannot rejoin the bidding again.
+            *turn.remove()
+        }))
+    }
+
     fn is_done(&self) -> bool {
         self.done
     }
@@ -171,25 +304,42 @@ impl Bidding for Bidder {
     }
 }
 
+// Replays the recorded bidding actions starting with the dealer.
+// Returns the bidder after all actions are applied or the index of the first
+// illegal action together with the error.
+pub fn replay(dealer: PlayerId, actions: &[BidAction]) -> Result<Bidder, (uint, BidError)> {
+    let mut bidder = Bidder::new(dealer);
+    for (i, action) in actions.iter().enumerate() {
+        let result = match *action {
+            PlayerBid(player, contract) => bidder.bid(&player, contract),
+            PlayerPass(player) => bidder.pass(&player),
+        };
+        match result {
+            Ok(_) => {}
+            Err(err) => return Err((i, err)),
+        }
+    }
+    Ok(bidder)
+}
+
 fn player_priority(turn: &PlayerTurn, player: &PlayerId) -> uint {
     let pos_diff = *player as uint - *turn.started_with() as uint;
     (pos_diff + turn.num_players() - 1) % turn.num_players()
 }
 
 fn is_bid_valid(highest: &Bid, wanted: &Bid) -> bool {
-    if wanted.player_priority <= highest.player_priority {
-        wanted.contract >= highest.contract
-    } else {
-        wanted.contract > highest.contract
-    }
+    let has_priority = wanted.player_priority <= highest.player_priority;
+    highest.contract.beaten_by(&wanted.contract, false) ||
+        highest.contract.held_by(&wanted.contract, has_priority)
 }
 
 #[cfg(test)]
 mod test {
     use super::{Bidder, Bidding, Next, Last, NotPlayersTurn,
-        MustBid, Done, InvalidContract, ContractTooLow};
+        MustBid, Done, InvalidContract, ContractTooLow, replay, PlayerBid, PlayerPass};
 
-    use super::DEFAULT_CONTRACT;
+    use super::{DEFAULT_CONTRACT, HandStrength, is_reasonable_bid};
+    use cards::*;
     use contracts::{KLOP, STANDARD_THREE, STANDARD_TWO, STANDARD_ONE,
         SOLO_THREE, SOLO_TWO, SOLO_ONE};
 
@@ -306,4 +456,87 @@ mod test {
         assert_eq!(bidder.pass(&2), Err(MustBid));
         assert_eq!(bidder.bid(&2, STANDARD_ONE), Ok(Last));
     }
+
+    #[test]
+    fn forehand_bid_after_all_players_pass_finishes_bidding() {
+        let mut bidder = Bidder::new(0);
+        assert_eq!(bidder.pass(&2), Ok(Next(3)));
+        assert!(!bidder.is_stalemate());
+        assert_eq!(bidder.pass(&3), Ok(Next(0)));
+        assert!(!bidder.is_stalemate());
+        assert_eq!(bidder.pass(&0), Ok(Next(1)));
+        assert!(!bidder.is_stalemate());
+        assert_eq!(bidder.pass(&1), Err(MustBid));
+        assert_eq!(bidder.bid(&1, STANDARD_THREE), Ok(Last));
+        assert!(bidder.is_done());
+        assert!(!bidder.is_stalemate());
+    }
+
+    #[test]
+    fn bidding_can_be_replayed() {
+        let actions = [PlayerBid(2, STANDARD_TWO), PlayerPass(3), PlayerPass(0),
+                       PlayerBid(1, STANDARD_TWO), PlayerPass(2), PlayerBid(1, STANDARD_TWO)];
+        let bidder = replay(0, &actions).unwrap();
+        assert!(bidder.is_done());
+        assert_eq!(bidder.winner().unwrap().player(), 1);
+        assert_eq!(bidder.winner().unwrap().contract(), STANDARD_TWO);
+    }
+
+    #[test]
+    fn replay_reports_the_first_illegal_action() {
+        let actions = [PlayerBid(2, STANDARD_TWO), PlayerPass(0), PlayerPass(3)];
+        match replay(0, &actions) {
+            Err(err) => assert_eq!(err, (1, NotPlayersTurn)),
+            Ok(_) => fail!("illegal pass was accepted"),
+        }
+    }
+
+    #[test]
+    fn kontra_of_the_winning_bid_is_reported_by_finalize() {
+        let mut bidder = Bidder::new(0);
+        assert_eq!(bidder.bid_with_kontra(&2, STANDARD_TWO), Ok(Next(3)));
+        assert_eq!(bidder.finalize(), None);
+        assert_eq!(bidder.pass(&3), Ok(Next(0)));
+        assert_eq!(bidder.pass(&0), Ok(Next(1)));
+        assert_eq!(bidder.pass(&1), Ok(Next(2)));
+        assert_eq!(bidder.bid_with_kontra(&2, STANDARD_TWO), Ok(Last));
+        let result = bidder.finalize().unwrap();
+        assert_eq!(result.declarer(), 2);
+        assert_eq!(result.contract(), STANDARD_TWO);
+        assert!(result.kontra());
+    }
+
+    #[test]
+    fn solo_one_is_reasonable_only_with_many_tarocks() {
+        let weak = Hand::new([CARD_TAROCK_2, CARD_TAROCK_7, CARD_HEARTS_KING, CARD_HEARTS_SEVEN,
+                              CARD_CLUBS_EIGHT, CARD_CLUBS_NINE, CARD_SPADES_QUEEN, CARD_SPADES_TEN,
+                              CARD_DIAMONDS_JACK, CARD_DIAMONDS_SEVEN, CARD_DIAMONDS_EIGHT, CARD_CLUBS_SEVEN]);
+        let strong = Hand::new([CARD_TAROCK_SKIS, CARD_TAROCK_MOND, CARD_TAROCK_20, CARD_TAROCK_19,
+                                CARD_TAROCK_12, CARD_TAROCK_10, CARD_TAROCK_6, CARD_TAROCK_3,
+                                CARD_HEARTS_KING, CARD_CLUBS_KING, CARD_SPADES_SEVEN, CARD_DIAMONDS_SEVEN]);
+        assert_eq!(HandStrength::new(&strong), HandStrength { tarocks: 8, high_tarocks: 4, kings: 2 });
+        assert!(!is_reasonable_bid(&SOLO_ONE, &HandStrength::new(&weak)));
+        assert!(is_reasonable_bid(&SOLO_ONE, &HandStrength::new(&strong)));
+    }
+
+    #[test]
+    fn forehand_wins_with_the_default_contract_when_everyone_passes() {
+        let mut bidder = Bidder::new(0);
+        assert_eq!(bidder.pass(&2), Ok(Next(3)));
+        assert_eq!(bidder.pass(&3), Ok(Next(0)));
+        assert_eq!(bidder.pass(&0), Ok(Next(1)));
+        assert!(!bidder.won_by_default());
+        assert_eq!(bidder.bid(&1, DEFAULT_CONTRACT), Ok(Last));
+        let winner = bidder.winner().unwrap();
+        assert_eq!(winner.player(), 1);
+        assert_eq!(winner.contract(), STANDARD_THREE);
+        assert!(bidder.won_by_default());
+
+        let mut bidder = Bidder::new(0);
+        assert_eq!(bidder.pass(&2), Ok(Next(3)));
+        assert_eq!(bidder.pass(&3), Ok(Next(0)));
+        assert_eq!(bidder.pass(&0), Ok(Next(1)));
+        assert_eq!(bidder.bid(&1, KLOP), Ok(Last));
+        assert!(!bidder.won_by_default());
+    }
 }
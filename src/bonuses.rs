@@ -1,5 +1,5 @@
 use cards::{Card, TarockCard, Tarock1, Tarock21, TarockSkis, SuitCard,
-    Clubs, Spades, Hearts, Diamonds, King, CardSuit, CARD_TAROCK_PAGAT};
+    Clubs, Spades, Hearts, Diamonds, King, CardSuit, Hand, CARD_TAROCK_PAGAT};
 use player::Player;
 
 use std::collections::HashSet;
@@ -15,7 +15,7 @@ pub static BONUS_TYPES: [BonusType, ..5] = [
 ];
 
 // Type of point bonus.
-#[deriving(Clone, Show, Eq, PartialEq, Hash)]
+#[deriving(Clone, Show, Eq, PartialEq, Hash, Encodable, Decodable)]
 pub enum BonusType {
     Trula,
     Kings,
@@ -35,6 +35,17 @@ impl BonusType {
             Valat => 250,
         }
     }
+
+    // Human readable name of bonus.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Trula => "Trula",
+            Kings => "Kings",
+            KingUltimo => "King Ultimo",
+            PagatUltimo => "Pagat Ultimo",
+            Valat => "Valat",
+        }
+    }
 }
 
 // Bonunes are additional ways to earn points.
@@ -54,6 +65,13 @@ impl Bonus {
         }
     }
 
+    // Returns the type of bonus.
+    pub fn bonus_type(&self) -> BonusType {
+        match *self {
+            Unannounced(bt) | Announced(bt) => bt,
+        }
+    }
+
     // Returns true if bonus is announced.
     pub fn is_announced(&self) -> bool {
         match *self {
@@ -63,6 +81,42 @@ impl Bonus {
     }
 }
 
+// Returns the net value of bonuses for settlement.
+// Achieved announced bonuses count double, failed announced bonuses count
+// double against and achieved unannounced bonuses count once.
+pub fn net_bonus(announced: &HashSet<BonusType>, achieved: &HashSet<BonusType>) -> int {
+    let announced_total = announced.iter()
+        .map(|bonus| {
+            let value = Announced(*bonus).value();
+            if achieved.contains(bonus) { value } else { -value }
+        })
+        .fold(0, |a, b| a + b);
+    let unannounced_total = achieved.difference(announced)
+        .map(|bonus| Unannounced(*bonus).value())
+        .fold(0, |a, b| a + b);
+    announced_total + unannounced_total
+}
+
+// Returns the total value of the announced and the unannounced bonuses.
+pub fn split_values(bonuses: &[Bonus]) -> (int, int) {
+    let announced = bonuses.iter()
+        .filter(|bonus| bonus.is_announced())
+        .map(|bonus| bonus.value())
+        .fold(0, |a, b| a + b);
+    let unannounced = bonuses.iter()
+        .filter(|bonus| !bonus.is_announced())
+        .map(|bonus| bonus.value())
+        .fold(0, |a, b| a + b);
+    (announced, unannounced)
+}
+
+// Advises whether the bonus should be announced. This is a baseline that
+// recommends announcing a bonus only when it is guaranteed, whatever its type:
+// an announced bonus is worth double, but a failed one counts double against.
+pub fn should_announce(_bonus: BonusType, guaranteed: bool) -> bool {
+    guaranteed
+}
+
 // Checks if cards contain a trula.
 pub fn has_trula(cards: &[Card]) -> bool {
     let mut pagat = false;
@@ -110,35 +164,45 @@ pub fn has_kings(cards: &[Card]) -> bool {
 
 // Returns a set of valid bonuses for the player.
 pub fn valid_bonuses(player: &Player, king: Option<CardSuit>) -> HashSet<BonusType> {
+    hand_bonuses(player.hand(), king)
+}
+
+// Returns true if all announced bonuses are valid for the hand.
+pub fn announcement_consistent(announced: &HashSet<BonusType>, hand: &Hand, king: Option<CardSuit>) -> bool {
+    announced.is_subset(&hand_bonuses(hand, king))
+}
+
+// Returns a set of valid bonuses for the hand.
+fn hand_bonuses(hand: &Hand, king: Option<CardSuit>) -> HashSet<BonusType> {
     let mut bonuses = HashSet::new();
     // Always valid bonuses.
     bonuses.insert(Trula);
     bonuses.insert(Kings);
     bonuses.insert(Valat);
-    if has_king(player, king) {
+    if has_king(hand, king) {
         bonuses.insert(KingUltimo);
     }
-    if has_pagat(player) {
+    if has_pagat(hand) {
         bonuses.insert(PagatUltimo);
     }
     return bonuses
 }
 
-// Returns true if the player owns the king of specified suit.
+// Returns true if the hand contains the king of specified suit.
 // If no king is given it always returns false.
-fn has_king(player: &Player, king: Option<CardSuit>) -> bool {
-   king.map(|suit| player.hand().has_card(&SuitCard(King, suit))).unwrap_or(false)
+fn has_king(hand: &Hand, king: Option<CardSuit>) -> bool {
+   king.map(|suit| hand.has_card(&SuitCard(King, suit))).unwrap_or(false)
 }
 
-// Returns true if the player owns the pagat card.
-fn has_pagat(player: &Player) -> bool {
-    player.hand().has_card(&CARD_TAROCK_PAGAT)
+// Returns true if the hand contains the pagat card.
+fn has_pagat(hand: &Hand) -> bool {
+    hand.has_card(&CARD_TAROCK_PAGAT)
 }
 
 #[cfg(test)]
 mod test {
     use super::{BONUS_TYPES, Unannounced, Announced, has_trula, has_kings,
-        valid_bonuses, Trula, Kings, Valat, KingUltimo, PagatUltimo};
+        valid_bonuses, net_bonus, should_announce, announcement_consistent, split_values, Trula, Kings, Valat, KingUltimo, PagatUltimo};
 
     use cards::*;
     use player::Player;
@@ -195,4 +259,32 @@ mod test {
         let player = Player::new(0, hand);
         assert_eq!(valid_bonuses(&player, Some(Hearts)), set![Trula, Kings, Valat, PagatUltimo]);
     }
+
+    #[test]
+    fn net_bonus_counts_announced_bonuses_double() {
+        assert_eq!(net_bonus(&set![Trula, PagatUltimo], &set![Trula]), -30);
+        assert_eq!(net_bonus(&set![], &set![Trula, Kings]), 20);
+        assert_eq!(net_bonus(&set![Kings], &set![Trula, Kings]), 30);
+    }
+
+    #[test]
+    fn only_guaranteed_bonuses_should_be_announced() {
+        assert!(should_announce(Kings, true));
+        assert!(!should_announce(PagatUltimo, false));
+    }
+
+    #[test]
+    fn king_ultimo_announcement_is_inconsistent_without_the_king() {
+        let hand = Hand::new([CARD_CLUBS_KING, CARD_TAROCK_10, CARD_HEARTS_NINE]);
+        assert!(!announcement_consistent(&set![KingUltimo], &hand, Some(Hearts)));
+        assert!(announcement_consistent(&set![KingUltimo], &hand, Some(Clubs)));
+        assert!(announcement_consistent(&set![Trula, Kings], &hand, Some(Hearts)));
+    }
+
+    #[test]
+    fn bonus_values_are_split_into_announced_and_unannounced() {
+        let bonuses = [Announced(Trula), Unannounced(Kings), Announced(PagatUltimo), Unannounced(KingUltimo)];
+        assert_eq!(split_values(bonuses), (70, 20));
+        assert_eq!(split_values([]), (0, 0));
+    }
 }
@@ -1,13 +1,20 @@
+use std::default::Default;
 use std::fmt;
 use std::fmt::{Formatter, Show};
+use std::from_str::FromStr;
+use std::hash;
 use std::hash::Hash;
 use std::iter::AdditiveIterator;
 
 use std::collections::HashSet;
 use std::collections::hashmap::SetItems;
-use std::rand::Rng;
+use std::rand::{Rng, SeedableRng, StdRng};
 
-#[deriving(Clone, Show, Eq, PartialEq, Hash)]
+use bonuses;
+use contracts;
+use player::PlayerId;
+
+#[deriving(Clone, Show, Eq, PartialEq, Hash, Encodable, Decodable)]
 pub enum CardSuit {
     Clubs,
     Spades,
@@ -15,7 +22,7 @@ pub enum CardSuit {
     Diamonds,
 }
 
-#[deriving(Clone, Show, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[deriving(Clone, Show, Eq, PartialEq, Hash, Ord, PartialOrd, Encodable, Decodable)]
 pub enum CardRank {
     Seven,
     Eight,
@@ -27,7 +34,7 @@ pub enum CardRank {
     King,
 }
 
-#[deriving(Clone, Show, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[deriving(Clone, Show, Eq, PartialEq, Hash, Ord, PartialOrd, Encodable, Decodable)]
 pub enum Tarock {
     Tarock1,
     Tarock2,
@@ -53,9 +60,96 @@ pub enum Tarock {
     TarockSkis,
 }
 
+// All suits in the order of `CARDS`.
+pub static ALL_SUITS: [CardSuit, ..4] = [Clubs, Spades, Hearts, Diamonds];
+
+// All tarocks in order, from the pagat to the skis.
+static ALL_TAROCKS: [Tarock, ..NUM_TAROCKS] = [
+    Tarock1, Tarock2, Tarock3, Tarock4, Tarock5, Tarock6, Tarock7, Tarock8, Tarock9, Tarock10, Tarock11,
+    Tarock12, Tarock13, Tarock14, Tarock15, Tarock16, Tarock17, Tarock18, Tarock19, Tarock20, Tarock21,
+    TarockSkis,
+];
+
+impl CardRank {
+    // Returns the 0-based index of the rank within its suit, from 0 for Seven
+    // to 7 for King.
+    pub fn index(&self) -> uint {
+        *self as uint
+    }
+}
+
+impl Tarock {
+    // Returns all tarocks in order, from the pagat to the skis.
+    pub fn all() -> &'static [Tarock] {
+        ALL_TAROCKS.as_slice()
+    }
+
+    // Returns the 0-based index of the tarock, from 0 for Pagat to 21 for Skis.
+    pub fn index(&self) -> uint {
+        *self as uint
+    }
+}
+
+pub mod naming {
+    use std::default::Default;
+
+    // How the highest tarock (the skis) is named when cards are rendered or
+    // parsed. Some rule sets treat the skis as the 22nd tarock.
+    #[deriving(Clone, Show, Eq, PartialEq)]
+    pub enum TarockNaming {
+        // "Skis"
+        Skis,
+        // "XXII"
+        Roman,
+        // "22"
+        Numeric,
+    }
+
+    impl TarockNaming {
+        // Returns the name of the skis under this naming.
+        pub fn skis_name(&self) -> &'static str {
+            match *self {
+                Skis => "Skis",
+                Roman => "XXII",
+                Numeric => "22",
+            }
+        }
+
+        // Returns true if the name denotes the skis under this naming.
+        // Letters are compared case-insensitively.
+        pub fn is_skis_name(&self, name: &str) -> bool {
+            super::to_upper(name).as_slice() == super::to_upper(self.skis_name()).as_slice()
+        }
+    }
+
+    impl Default for TarockNaming {
+        fn default() -> TarockNaming {
+            Skis
+        }
+    }
+}
+
+pub use self::naming::TarockNaming;
+
 pub const NUM_CARDS: uint = 54;
 
-#[deriving(Clone, Show, Eq, PartialEq, Hash)]
+pub const NUM_TAROCKS: uint = 22;
+
+// Index of the first tarock (pagat) in `CARDS`.
+const FIRST_TAROCK_INDEX: uint = 32;
+
+// An error parsing a card from its notation.
+#[deriving(Clone, Show, Eq, PartialEq)]
+pub enum ParseCardError {
+    // The suit is not one of C, S, H or D.
+    UnknownSuit,
+    // The rank is not one of 7, 8, 9, 10, J, N, Q or K.
+    UnknownRank,
+    // The tarock number is not between 1 and 21.
+    TarockOutOfRange,
+}
+
+#[deriving(Clone, Show, Eq, PartialEq, Hash, Encodable, Decodable)]
 pub enum Card {
     TarockCard(Tarock),
     SuitCard(CardRank, CardSuit),
@@ -90,6 +184,13 @@ impl Card {
         }
     }
 
+    pub fn is_king(&self) -> bool {
+        match *self {
+            SuitCard(King, _) => true,
+            _ => false,
+        }
+    }
+
     pub fn is_valuable(&self) -> bool {
         self.value() > 0
     }
@@ -124,6 +225,230 @@ impl Card {
             }
         }
     }
+
+    // Returns the short notation of the card: the suit letter followed by the
+    // rank for suit cards (e.g. "HK", "D7", "C10") and "T" followed by the
+    // number for tarocks (e.g. "T1", "T21") except for the skis ("SKIS").
+    pub fn notation(&self) -> String {
+        match *self {
+            SuitCard(rank, suit) => format!("{}{}", suit_notation(suit), rank_notation(rank)),
+            TarockCard(TarockSkis) => "SKIS".to_string(),
+            TarockCard(tarock) => format!("T{}", tarock.index() + 1),
+        }
+    }
+
+    // Returns the symbol of the card used in logs: the rank followed by the suit
+    // symbol for suit cards (e.g. "K♠") and roman numerals for tarocks (e.g.
+    // "XX") except for the skis which is named with the default naming.
+    pub fn symbol(&self) -> String {
+        self.symbol_with(Default::default())
+    }
+
+    // Returns the symbol of the card with the skis named according to the
+    // naming.
+    pub fn symbol_with(&self, naming: TarockNaming) -> String {
+        match *self {
+            SuitCard(rank, suit) => format!("{}{}", rank_notation(rank), suit_symbol(suit)),
+            TarockCard(TarockSkis) => naming.skis_name().to_string(),
+            TarockCard(tarock) => roman_numeral(tarock.index() + 1),
+        }
+    }
+
+    // Returns the position of the card in `CARDS`, a stable index in 0..54.
+    pub fn index(&self) -> uint {
+        match *self {
+            SuitCard(rank, suit) => 8 * (suit as uint) + rank.index(),
+            TarockCard(tarock) => FIRST_TAROCK_INDEX + tarock.index(),
+        }
+    }
+
+    // Returns the card at the index in `CARDS` or `None` if the index is out
+    // of range.
+    pub fn from_index(index: uint) -> Option<Card> {
+        if index < NUM_CARDS {
+            Some(CARDS[index])
+        } else {
+            None
+        }
+    }
+
+    // Returns a helper for formatting the card with its human readable name,
+    // e.g. "King of Hearts" or "Tarock XXI (Mond)".
+    pub fn display<'a>(&'a self) -> Display<'a> {
+        self.display_with(Default::default())
+    }
+
+    // Returns a helper for formatting the card with its human readable name
+    // and the skis named according to the naming.
+    pub fn display_with<'a>(&'a self, naming: TarockNaming) -> Display<'a> {
+        Display { card: self, naming: naming }
+    }
+
+    // Parses a card from its short notation. Letters are case-insensitive and
+    // the pagat and the mond can also be named "PAGAT" and "MOND". The skis is
+    // parsed with the default naming.
+    pub fn from_notation(notation: &str) -> Result<Card, ParseCardError> {
+        Card::from_notation_with(notation, Default::default())
+    }
+
+    // Parses a card from its short notation. Besides "SKIS" the skis is also
+    // parsed from its name under the naming.
+    pub fn from_notation_with(notation: &str, naming: TarockNaming) -> Result<Card, ParseCardError> {
+        if naming.is_skis_name(notation) {
+            return Ok(CARD_TAROCK_SKIS)
+        }
+        let upper = to_upper(notation);
+        match upper.as_slice() {
+            "PAGAT" => return Ok(CARD_TAROCK_PAGAT),
+            "MOND" => return Ok(CARD_TAROCK_MOND),
+            "SKIS" => return Ok(CARD_TAROCK_SKIS),
+            _ => {}
+        }
+        let mut chars = upper.as_slice().chars();
+        let first = chars.next();
+        let rest = chars.collect::<String>();
+        match first {
+            Some('T') => {
+                match from_str::<uint>(rest.as_slice()) {
+                    Some(n) if n >= 1 && n <= 21 => Ok(CARDS[FIRST_TAROCK_INDEX + n - 1]),
+                    Some(_) => Err(TarockOutOfRange),
+                    None => Err(UnknownRank),
+                }
+            }
+            Some(suit) => {
+                let suit = try!(parse_suit(suit));
+                let rank = try!(parse_rank(rest.as_slice()));
+                Ok(SuitCard(rank, suit))
+            }
+            None => Err(UnknownSuit),
+        }
+    }
+}
+
+// Parses a card from its short notation, see `Card::from_notation` for the
+// reason why parsing failed.
+impl FromStr for Card {
+    fn from_str(s: &str) -> Option<Card> {
+        Card::from_notation(s).ok()
+    }
+}
+
+fn suit_notation(suit: CardSuit) -> &'static str {
+    match suit {
+        Clubs => "C",
+        Spades => "S",
+        Hearts => "H",
+        Diamonds => "D",
+    }
+}
+
+fn suit_name(suit: CardSuit) -> &'static str {
+    match suit {
+        Clubs => "Clubs",
+        Spades => "Spades",
+        Hearts => "Hearts",
+        Diamonds => "Diamonds",
+    }
+}
+
+fn rank_name(rank: CardRank) -> &'static str {
+    match rank {
+        Seven => "Seven",
+        Eight => "Eight",
+        Nine => "Nine",
+        Ten => "Ten",
+        Jack => "Jack",
+        Knight => "Knight",
+        Queen => "Queen",
+        King => "King",
+    }
+}
+
+// Formats the card with its human readable name. The derived `Show` of the
+// card prints the raw enum variant instead.
+pub struct Display<'a> {
+    card: &'a Card,
+    naming: TarockNaming,
+}
+
+impl<'a> Show for Display<'a> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match *self.card {
+            SuitCard(rank, suit) => write!(fmt, "{} of {}", rank_name(rank), suit_name(suit)),
+            TarockCard(Tarock1) => write!(fmt, "Tarock I (Pagat)"),
+            TarockCard(Tarock21) => write!(fmt, "Tarock XXI (Mond)"),
+            TarockCard(TarockSkis) => write!(fmt, "{}", self.naming.skis_name()),
+            TarockCard(tarock) => write!(fmt, "Tarock {}", roman_numeral(tarock.index() + 1)),
+        }
+    }
+}
+
+fn suit_symbol(suit: CardSuit) -> &'static str {
+    match suit {
+        Clubs => "♣",
+        Spades => "♠",
+        Hearts => "♥",
+        Diamonds => "♦",
+    }
+}
+
+// Returns the number written in roman numerals. Only numbers up to 39 are
+// supported, which is enough for tarocks.
+fn roman_numeral(n: uint) -> String {
+    let mut roman = String::new();
+    for _ in range(0, n / 10) {
+        roman.push_str("X");
+    }
+    let units = ["", "I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX"];
+    roman.push_str(units[n % 10]);
+    roman
+}
+
+fn rank_notation(rank: CardRank) -> &'static str {
+    match rank {
+        Seven => "7",
+        Eight => "8",
+        Nine => "9",
+        Ten => "10",
+        Jack => "J",
+        Knight => "N",
+        Queen => "Q",
+        King => "K",
+    }
+}
+
+fn parse_suit(suit: char) -> Result<CardSuit, ParseCardError> {
+    match suit {
+        'C' => Ok(Clubs),
+        'S' => Ok(Spades),
+        'H' => Ok(Hearts),
+        'D' => Ok(Diamonds),
+        _ => Err(UnknownSuit),
+    }
+}
+
+fn parse_rank(rank: &str) -> Result<CardRank, ParseCardError> {
+    match rank {
+        "7" => Ok(Seven),
+        "8" => Ok(Eight),
+        "9" => Ok(Nine),
+        "10" => Ok(Ten),
+        "J" => Ok(Jack),
+        "N" => Ok(Knight),
+        "Q" => Ok(Queen),
+        "K" => Ok(King),
+        _ => Err(UnknownRank),
+    }
+}
+
+fn to_upper(s: &str) -> String {
+    s.chars().map(|c| c.to_uppercase()).collect()
+}
+
+// Sorts the cards in the order of `CARDS`: suit cards by suit and rank followed
+// by tarocks.
+pub fn sort_cards(cards: &mut [Card]) {
+    cards.sort_by(|a, b| a.index().cmp(&b.index()));
 }
 
 impl PartialOrd for Card {
@@ -267,6 +592,52 @@ pub static CARDS: [Card, ..54] = [
     CARD_TAROCK_SKIS,
 ];
 
+// Returns all tarock cards in order, from the pagat to the skis.
+pub fn tarocks() -> [Card, ..NUM_TAROCKS] {
+    let mut tarocks = [CARD_TAROCK_PAGAT, ..NUM_TAROCKS];
+    for (tarock, card) in tarocks.iter_mut().zip(CARDS.slice_from(NUM_CARDS - NUM_TAROCKS).iter()) {
+        *tarock = *card;
+    }
+    tarocks
+}
+
+// A set of cards.
+pub type CardSet = HashSet<Card>;
+
+// Returns an upper bound of the points that can be captured with the remaining
+// cards, regardless of who holds them.
+// When counting in groups of three every valuable card is worth one point less
+// than its value and every card adds a third of a point.
+pub fn max_capturable(remaining: &CardSet) -> uint {
+    let value = remaining.iter().map(|card| card.value()).sum();
+    let num_valuable = remaining.iter().filter(|card| card.is_valuable()).count();
+    (3 * (value - num_valuable) + remaining.len() + 2) / 3
+}
+
+// Returns the cards that would beat the card under standard rules: higher
+// cards of the same suit and any tarock for a suit card, higher tarocks for a
+// tarock.
+pub fn cards_beating(card: &Card, among: &CardSet) -> CardSet {
+    among.iter()
+        .filter(|other| match (card.suit(), other.suit()) {
+            (Some(suit), Some(other_suit)) => suit == other_suit && *other > card,
+            (Some(_), None) => true,
+            (None, None) => *other > card,
+            (None, Some(_)) => false,
+        })
+        .map(|card| *card)
+        .collect()
+}
+
+// Returns the highest card of the suit that was not seen yet, that is the card
+// that would currently win a trick of that suit if no tarocks are played.
+pub fn boss_card(suit: CardSuit, seen: &HashSet<Card>) -> Option<Card> {
+    CARDS.iter()
+        .rev()
+        .find(|card| card.suit() == Some(suit) && !seen.contains(*card))
+        .map(|card| *card)
+}
+
 pub struct Cards<'a> {
     iter: SetItems<'a, Card>,
 }
@@ -297,6 +668,12 @@ impl Hand {
         }
     }
 
+    // Adds the card to the hand. Adding a card that is already in the hand
+    // has no effect.
+    pub fn add_card(&mut self, card: Card) {
+        self.cards.insert(card);
+    }
+
     pub fn remove_card(&mut self, card: &Card) {
         self.cards.remove(card);
     }
@@ -317,15 +694,88 @@ impl Hand {
         self.cards.iter().any(|card| card.suit() == Some(*suit))
     }
 
+    // Returns true if the hand has at least one card of every suit.
+    pub fn has_all_suits(&self) -> bool {
+        ALL_SUITS.iter().all(|suit| self.has_suit(suit))
+    }
+
     pub fn has_card(&self, card: &Card) -> bool {
         self.cards.contains(card)
     }
 
+    // Returns true if the pagat is the only tarock in the hand.
+    pub fn pagat_is_only_tarock(&self) -> bool {
+        self.has_card(&CARD_TAROCK_PAGAT) &&
+            self.cards.iter().filter(|card| card.is_tarock()).count() == 1
+    }
+
     pub fn cards<'a>(&'a self) -> Cards<'a> {
         Cards {
             iter: self.cards.iter(),
         }
     }
+
+    // Returns the cards of the hand in a canonical order as a space-separated
+    // list of card notations, e.g. "C10 HK T21 SKIS".
+    pub fn to_notation(&self) -> String {
+        let mut cards = self.cards().map(|c| *c).collect::<Vec<Card>>();
+        sort_cards(cards.as_mut_slice());
+        cards.iter().map(|card| card.notation()).collect::<Vec<String>>().connect(" ")
+    }
+
+    // Returns the number of kings in the hand.
+    pub fn king_count(&self) -> uint {
+        self.cards().filter(|card| card.is_king()).count()
+    }
+
+    // Returns the cards of the suit in the hand sorted by rank.
+    pub fn cards_of_suit(&self, suit: CardSuit) -> Vec<Card> {
+        let mut cards = self.cards().filter(|card| card.suit() == Some(suit)).map(|c| *c).collect::<Vec<Card>>();
+        sort_cards(cards.as_mut_slice());
+        cards
+    }
+
+    // Returns the tarocks in the hand in order from the pagat up.
+    pub fn tarocks(&self) -> Vec<Tarock> {
+        let mut tarocks = self.cards()
+            .filter_map(|card| match *card {
+                TarockCard(tarock) => Some(tarock),
+                _ => None,
+            })
+            .collect::<Vec<Tarock>>();
+        tarocks.sort();
+        tarocks
+    }
+
+    // Returns all the cards in the hand sorted, since any card can lead a trick.
+    pub fn possible_leads(&self) -> Vec<Card> {
+        self.sorted_cards()
+    }
+
+    // Returns the cards of the hand in a stable order: suit cards grouped by
+    // suit and ordered by rank, followed by tarocks. The order of `CARDS` is
+    // used since the ordering of `Card` does not order cards of different
+    // suits.
+    pub fn sorted_cards(&self) -> Vec<Card> {
+        let mut cards = self.cards().map(|c| *c).collect::<Vec<Card>>();
+        sort_cards(cards.as_mut_slice());
+        cards
+    }
+
+    // Returns the sorted cards from the hand that are valid responses to the trick.
+    pub fn possible_responses(&self, trick: &Trick,
+                              validator: fn(&Hand, &Trick, &Card) -> bool) -> Vec<Card> {
+        contracts::valid_moves_sorted(validator, self, trick)
+    }
+
+    // Parses a hand from a whitespace separated list of card notations.
+    pub fn from_notation(notation: &str) -> Result<Hand, ParseCardError> {
+        let mut cards = HashSet::new();
+        for card in notation.words() {
+            cards.insert(try!(Card::from_notation(card)));
+        }
+        Ok(Hand{ cards: cards })
+    }
 }
 
 pub const TALON_SIZE: uint = 6;
@@ -341,13 +791,30 @@ impl Talon {
         }
     }
 
-    fn cards(&self) -> &[Card] {
+    // Returns the cards in the talon.
+    pub fn cards(&self) -> &[Card] {
         self.cards.as_slice()
     }
 
-    fn size(&self) -> uint {
+    pub fn size(&self) -> uint {
         self.cards.len()
     }
+
+    // Splits the talon into consecutive parts of the given sizes, e.g. three
+    // parts of two cards for the exchange in a three player game.
+    // Fails if the sizes do not add up to the size of the talon.
+    pub fn split(&self, parts: &[uint]) -> Vec<&[Card]> {
+        assert!(parts.iter().map(|&part| part).sum() == self.size(),
+                "the parts do not add up to the size of the talon");
+        let mut offset = 0;
+        parts.iter()
+            .map(|&part| {
+                let cards = self.cards.slice(offset, offset + part);
+                offset += part;
+                cards
+            })
+            .collect()
+    }
 }
 
 pub struct CardDeal {
@@ -355,19 +822,55 @@ pub struct CardDeal {
     pub hands: Vec<Hand>,
 }
 
+// How the cards are split between the players and the talon.
+#[deriving(Clone, Show, Eq, PartialEq)]
+pub struct DealPlan {
+    // Number of cards each player gets.
+    pub hand_size: uint,
+    // Number of cards in the talon.
+    pub talon_size: uint,
+    // Sizes of the packets each player receives, in the order they are dealt.
+    pub packet_sizes: Vec<uint>,
+}
+
+#[deriving(Show, Eq, PartialEq)]
+pub enum DealError {
+    UnsupportedNumberOfPlayers,
+}
+
+// Returns the standard deal plan for the number of players.
+// Only 3 and 4 player games are supported.
+pub fn deal_plan(num_players: uint) -> Result<DealPlan, DealError> {
+    match num_players {
+        3 => Ok(DealPlan { hand_size: 16, talon_size: TALON_SIZE, packet_sizes: vec![8, 8] }),
+        4 => Ok(DealPlan { hand_size: 12, talon_size: TALON_SIZE, packet_sizes: vec![6, 6] }),
+        _ => Err(UnsupportedNumberOfPlayers),
+    }
+}
+
 pub fn deal_four_player_standard(cards: &[Card]) -> CardDeal {
-    const NUM_PLAYERS: uint = 4;
+    deal_standard(cards, 4)
+}
+
+pub fn deal_three_player_standard(cards: &[Card]) -> CardDeal {
+    deal_standard(cards, 3)
+}
 
-    let mut six_card_packets = cards.chunks(6);
-    let talon = six_card_packets.next().unwrap();
-    let mut hands = Vec::from_fn(NUM_PLAYERS, |_| {
+// Deals the talon first and then the packets to the players in turns as
+// specified by the deal plan for the number of players.
+fn deal_standard(cards: &[Card], num_players: uint) -> CardDeal {
+    let plan = deal_plan(num_players).unwrap();
+    let talon = cards.slice_to(plan.talon_size);
+    let mut hands = Vec::from_fn(num_players, |_| {
         Hand::empty()
     });
 
-    let mut player_index = 0;
-    for packet in six_card_packets {
-        insert_all(&mut hands.get_mut(player_index).cards, packet);
-        player_index = (player_index + 1) % NUM_PLAYERS;
+    let mut offset = plan.talon_size;
+    for &packet_size in plan.packet_sizes.iter() {
+        for hand in hands.iter_mut() {
+            insert_all(&mut hand.cards, cards.slice(offset, offset + packet_size));
+            offset += packet_size;
+        }
     }
 
     CardDeal {
@@ -376,6 +879,30 @@ pub fn deal_four_player_standard(cards: &[Card]) -> CardDeal {
     }
 }
 
+// Returns the point value of each hand of the deal, counted in groups of three
+// like a pile. Useful for checking that a shuffler deals the points evenly
+// over many deals.
+pub fn deal_balance(deal: &CardDeal) -> Vec<uint> {
+    deal.hands.iter()
+        .map(|hand| {
+            let mut pile = Pile::new();
+            for card in hand.sorted_cards().into_iter() {
+                pile.add_card(card);
+            }
+            pile.score() as uint
+        })
+        .collect()
+}
+
+// Returns a fingerprint of the deal computed from the talon and the sorted
+// hands. Equal deals always have the same fingerprint, so it can be used as a
+// seed or a key of test fixtures.
+pub fn deal_fingerprint(deal: &CardDeal) -> u64 {
+    let mut parts = vec![deal.talon.cards().to_vec()];
+    parts.extend(deal.hands.iter().map(|hand| hand.sorted_cards()));
+    hash::hash(&parts)
+}
+
 fn insert_all<T: Eq + Hash + Clone>(set: &mut HashSet<T>, xs: &[T]) {
     for x in xs.iter() {
         set.insert(x.clone());
@@ -405,6 +932,17 @@ impl<S> Deck<S> {
         Deck {cards: self.cards}
     }
 
+    // Shuffles the deck with a generator seeded with the seed. Decks shuffled
+    // with the same seed end up in the same order, so a deal can be
+    // reproduced from the seed alone.
+    pub fn shuffle_seeded(self, seed: u64) -> Deck<Shuffled> {
+        // Both halves go into the seed so that no bits are lost on targets
+        // where `uint` is 32 bits wide.
+        let seed: &[uint] = &[seed as uint, (seed >> 32) as uint];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        self.shuffle(&mut rng)
+    }
+
     pub fn size(&self) -> uint {
         self.cards.len()
     }
@@ -429,6 +967,7 @@ pub struct TrickWinner {
     pub card: Card,
 }
 
+#[deriving(Clone, Show, Eq, PartialEq)]
 pub struct Trick {
     cards: Vec<Card>,
 }
@@ -444,6 +983,10 @@ impl Trick {
         trick
     }
 
+    pub fn from_cards(cards: &[Card]) -> Trick {
+        Trick {cards: cards.to_vec()}
+    }
+
     pub fn add_card(&mut self, card: Card) {
         self.cards.push(card);
     }
@@ -472,6 +1015,29 @@ impl Trick {
         self.cards.as_slice()
     }
 
+    // Returns true if the pagat, the mond and the skis were all played in the trick.
+    pub fn has_trula(&self) -> bool {
+        bonuses::has_trula(self.cards.as_slice())
+    }
+
+    // Describes the trick with the players that played the cards, starting
+    // with the leader, e.g. "P2:K♠ P3:7♠ P0:XX P1:Q♠".
+    pub fn describe(&self, leader: PlayerId, num_players: uint) -> String {
+        self.cards.iter()
+            .enumerate()
+            .map(|(i, card)| {
+                let player = (leader as uint + i) % num_players;
+                format!("P{}:{}", player, card.symbol())
+            })
+            .collect::<Vec<String>>()
+            .connect(" ")
+    }
+
+    // Returns the number of tarocks played in the trick.
+    pub fn tarock_count(&self) -> uint {
+        self.cards.iter().filter(|card| card.is_tarock()).count()
+    }
+
     pub fn winner(&self, f: |&[Card]| -> uint) -> TrickWinner {
         let card_index = f(self.cards.as_slice());
         TrickWinner {
@@ -479,12 +1045,32 @@ impl Trick {
             card: self.cards[card_index],
         }
     }
+
+    // Returns the card currently winning the trick, evaluated against the
+    // cards played so far. Returns `None` if no card was played yet.
+    pub fn current_winner(&self, f: |&[Card]| -> uint) -> Option<TrickWinner> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.winner(f))
+        }
+    }
 }
 
 pub const MAX_POINTS: int = 70;
 
 pub const HALF_POINTS: int = 35;
 
+// Returns the points of the whole deck counted as a single pile. This is
+// always `MAX_POINTS` unless the scoring rules change.
+pub fn deck_point_total() -> uint {
+    let mut pile = Pile::new();
+    for card in CARDS.iter() {
+        pile.add_card(*card);
+    }
+    pile.score() as uint
+}
+
 #[deriving(Clone)]
 pub struct Pile {
     cards: Vec<Card>,
@@ -511,6 +1097,11 @@ impl Pile {
         }
     }
 
+    // Returns the cards in the pile in the order they were added.
+    pub fn cards(&self) -> &[Card] {
+        self.cards.as_slice()
+    }
+
     pub fn size(&self) -> uint {
         self.cards.len()
     }
@@ -519,25 +1110,68 @@ impl Pile {
         self.size() == 0
     }
 
+    // Returns true if the pile contains the pagat, the mond and the skis.
+    pub fn has_trula(&self) -> bool {
+        bonuses::has_trula(self.cards.as_slice())
+    }
+
+    // Returns true if the pile contains all four kings.
+    pub fn has_all_kings(&self) -> bool {
+        bonuses::has_kings(self.cards.as_slice())
+    }
+
     pub fn score(&self) -> int {
-        let mut total = 0i;
-        for group in self.cards.as_slice().chunks(3) {
-            let score = group.iter().map(|c| c.value()).sum() as int;
-            let num_valuable = group.iter().filter(|c| c.is_valuable()).count();
-            if group.len() > 1 {
-                if score == 0 {
-                    total += 1;
-                } else {
-                    total += score - (num_valuable as int - 1);
-                }
-            } else if num_valuable > 0 {
-                total += score - 1;
-            }
+        self.score_with_group_size(3)
+    }
+
+    // Scores the pile counting the cards in groups of the given size instead
+    // of the usual groups of three.
+    pub fn score_with_group_size(&self, group: uint) -> int {
+        self.cards.as_slice().chunks(group).map(|group| group_score(group)).sum()
+    }
+
+    // Scores the pile and returns the score of every group of three cards
+    // together with the total.
+    pub fn score_detailed(&self) -> ScoreBreakdown {
+        let groups = self.cards.as_slice()
+            .chunks(3)
+            .map(|group| group_score(group))
+            .collect::<Vec<int>>();
+        let total = groups.iter().map(|&score| score).sum();
+        ScoreBreakdown {
+            groups: groups,
+            total: total,
         }
-        total
     }
 }
 
+// Returns the score of a group of cards. Every valuable card is worth one
+// point less than its value and a group of more than one card adds a point.
+fn group_score(group: &[Card]) -> int {
+    let score = group.iter().map(|c| c.value()).sum() as int;
+    let num_valuable = group.iter().filter(|c| c.is_valuable()).count();
+    if group.len() > 1 {
+        if score == 0 {
+            1
+        } else {
+            score - (num_valuable as int - 1)
+        }
+    } else if num_valuable > 0 {
+        score - 1
+    } else {
+        0
+    }
+}
+
+// Score of a pile broken down into groups of three cards in the order they
+// were added to the pile.
+#[deriving(Clone, Show, Eq, PartialEq)]
+pub struct ScoreBreakdown {
+    // Score of every group, the last group may have less than three cards.
+    pub groups: Vec<int>,
+    pub total: int,
+}
+
 #[cfg(test)]
 mod test {
     use quickcheck::{Arbitrary, Gen};
@@ -681,6 +1315,36 @@ mod test {
         num_cards_in_deck == num_cards
     }
 
+    #[quickcheck]
+    fn all_cards_are_unique_with_three_player_standard_deal_strategy(deck: Deck<Shuffled>) -> bool {
+        let num_cards_in_deck = Deck::new().cards.len();
+        let dealt_cards = deck.deal(deal_three_player_standard);
+        let mut card_set = HashSet::new();
+        insert_all(&mut card_set, dealt_cards.talon.cards());
+        for hand in dealt_cards.hands.iter() {
+            let cards = hand.cards().map(|c| *c).collect::<Vec<_>>();
+            insert_all(&mut card_set, cards.as_slice());
+        }
+        num_cards_in_deck == card_set.len()
+    }
+
+    #[quickcheck]
+    fn all_cards_are_dealt_with_three_player_standard_deal_strategy(deck: Deck<Shuffled>) -> bool {
+        let num_cards_in_deck = Deck::new().cards.len();
+        let dealt_cards = deck.deal(deal_three_player_standard);
+        let num_cards = dealt_cards.talon.size() +
+            dealt_cards.hands.iter().map(|h| h.size()).sum();
+        num_cards_in_deck == num_cards
+    }
+
+    #[test]
+    fn three_player_standard_deal_gives_sixteen_cards_to_each_player() {
+        let dealt_cards = deal_three_player_standard(&CARDS);
+        assert_eq!(dealt_cards.talon.size(), 6);
+        assert_eq!(dealt_cards.hands.len(), 3);
+        assert!(dealt_cards.hands.iter().all(|hand| hand.size() == 16));
+    }
+
     #[test]
     fn total_score_of_a_deck_is_70() {
         let deck = Deck::new();
@@ -717,4 +1381,377 @@ mod test {
         trick.clear();
         assert_eq!(trick.count(), 0);
     }
+
+    #[test]
+    fn trick_from_cards_keeps_the_order_of_cards() {
+        let mut trick = Trick::empty();
+        trick.add_card(CARD_SPADES_KING);
+        trick.add_card(CARD_TAROCK_PAGAT);
+        trick.add_card(CARD_SPADES_SEVEN);
+        assert_eq!(Trick::from_cards([CARD_SPADES_KING, CARD_TAROCK_PAGAT, CARD_SPADES_SEVEN]), trick);
+        assert!(Trick::from_cards([CARD_TAROCK_PAGAT, CARD_SPADES_KING, CARD_SPADES_SEVEN]) != trick);
+    }
+
+    #[test]
+    fn boss_card_is_the_highest_card_not_seen() {
+        let mut seen = HashSet::new();
+        assert_eq!(boss_card(Spades, &seen), Some(CARD_SPADES_KING));
+        seen.insert(CARD_SPADES_KING);
+        seen.insert(CARD_HEARTS_QUEEN);
+        assert_eq!(boss_card(Spades, &seen), Some(CARD_SPADES_QUEEN));
+        for card in CARDS.iter().filter(|card| card.suit() == Some(Spades)) {
+            seen.insert(*card);
+        }
+        assert_eq!(boss_card(Spades, &seen), None);
+    }
+
+    #[test]
+    fn skis_is_named_according_to_the_naming() {
+        use std::default::Default;
+
+        let default: TarockNaming = Default::default();
+        assert_eq!(default, naming::Skis);
+        assert_eq!(naming::Skis.skis_name(), "Skis");
+        assert_eq!(naming::Roman.skis_name(), "XXII");
+        assert_eq!(naming::Numeric.skis_name(), "22");
+        assert!(naming::Skis.is_skis_name("SKIS"));
+        assert!(naming::Roman.is_skis_name("xxii"));
+        assert!(!naming::Numeric.is_skis_name("21"));
+    }
+
+    #[test]
+    fn skis_is_formatted_and_parsed_according_to_the_naming() {
+        for &(naming, name) in [(naming::Skis, "Skis"), (naming::Roman, "XXII"), (naming::Numeric, "22")].iter() {
+            assert_eq!(format!("{}", CARD_TAROCK_SKIS.display_with(naming)).as_slice(), name);
+            assert_eq!(CARD_TAROCK_SKIS.symbol_with(naming).as_slice(), name);
+            assert_eq!(Card::from_notation_with(name, naming), Ok(CARD_TAROCK_SKIS));
+            assert_eq!(Card::from_notation_with("SKIS", naming), Ok(CARD_TAROCK_SKIS));
+        }
+        assert_eq!(Card::from_notation_with("22", naming::Skis), Err(UnknownSuit));
+        assert_eq!(Card::from_notation("XXII"), Err(UnknownSuit));
+        assert_eq!(format!("{}", CARD_TAROCK_SKIS.display()).as_slice(), "Skis");
+        assert_eq!(from_str::<Card>("skis"), Some(CARD_TAROCK_SKIS));
+    }
+
+    #[test]
+    fn deal_plan_for_three_players() {
+        let plan = deal_plan(3).unwrap();
+        assert_eq!(plan.hand_size, 16);
+        assert_eq!(plan.talon_size, 6);
+        assert_eq!(plan.packet_sizes, vec![8, 8]);
+    }
+
+    #[test]
+    fn deal_plan_for_four_players() {
+        let plan = deal_plan(4).unwrap();
+        assert_eq!(plan.hand_size, 12);
+        assert_eq!(plan.talon_size, 6);
+        assert_eq!(plan.packet_sizes, vec![6, 6]);
+    }
+
+    #[test]
+    fn deal_plan_is_not_available_for_other_player_counts() {
+        assert_eq!(deal_plan(2), Err(UnsupportedNumberOfPlayers));
+        assert_eq!(deal_plan(5), Err(UnsupportedNumberOfPlayers));
+    }
+
+    #[test]
+    fn every_card_can_be_parsed_from_its_notation() {
+        for card in CARDS.iter() {
+            assert_eq!(Card::from_notation(card.notation().as_slice()), Ok(*card));
+        }
+    }
+
+    #[test]
+    fn card_notation_is_case_insensitive() {
+        assert_eq!(Card::from_notation("hk"), Ok(CARD_HEARTS_KING));
+        assert_eq!(Card::from_notation("d10"), Ok(CARD_DIAMONDS_TEN));
+        assert_eq!(Card::from_notation("Pagat"), Ok(CARD_TAROCK_PAGAT));
+        assert_eq!(Card::from_notation("t21"), Ok(CARD_TAROCK_MOND));
+    }
+
+    #[test]
+    fn invalid_card_notation_is_rejected() {
+        assert_eq!(Card::from_notation(""), Err(UnknownSuit));
+        assert_eq!(Card::from_notation("XK"), Err(UnknownSuit));
+        assert_eq!(Card::from_notation("H6"), Err(UnknownRank));
+        assert_eq!(Card::from_notation("T0"), Err(TarockOutOfRange));
+        assert_eq!(Card::from_notation("T22"), Err(TarockOutOfRange));
+    }
+
+    #[test]
+    fn hand_notation_round_trips() {
+        let hand = Hand::new([CARD_TAROCK_SKIS, CARD_HEARTS_KING, CARD_TAROCK_MOND,
+                              CARD_DIAMONDS_SEVEN, CARD_CLUBS_TEN]);
+        assert_eq!(hand.to_notation().as_slice(), "C10 HK D7 T21 SKIS");
+        assert_eq!(Hand::from_notation(hand.to_notation().as_slice()), Ok(hand));
+        assert_eq!(Hand::from_notation("HK X7"), Err(UnknownSuit));
+    }
+
+    #[test]
+    fn tarocks_in_trick_are_counted() {
+        assert_eq!(Trick::empty().tarock_count(), 0);
+        let trick = Trick::from_cards([CARD_SPADES_KING, CARD_TAROCK_PAGAT, CARD_SPADES_SEVEN, CARD_TAROCK_SKIS]);
+        assert_eq!(trick.tarock_count(), 2);
+    }
+
+    #[test]
+    fn pagat_is_only_tarock_in_hand() {
+        assert!(Hand::new([CARD_TAROCK_PAGAT, CARD_HEARTS_KING]).pagat_is_only_tarock());
+        assert!(!Hand::new([CARD_TAROCK_PAGAT, CARD_TAROCK_2]).pagat_is_only_tarock());
+        assert!(!Hand::new([CARD_TAROCK_2, CARD_HEARTS_KING]).pagat_is_only_tarock());
+    }
+
+    #[test]
+    fn ranks_and_tarocks_are_indexed_in_order() {
+        assert_eq!(Seven.index(), 0);
+        assert_eq!(Ten.index(), 3);
+        assert_eq!(King.index(), 7);
+        assert_eq!(Tarock1.index(), 0);
+        assert_eq!(Tarock12.index(), 11);
+        assert_eq!(Tarock21.index(), 20);
+        assert_eq!(TarockSkis.index(), 21);
+    }
+
+    #[test]
+    fn trick_with_pagat_mond_and_skis_has_trula() {
+        assert!(Trick::from_cards([CARD_TAROCK_MOND, CARD_TAROCK_SKIS, CARD_TAROCK_2, CARD_TAROCK_PAGAT]).has_trula());
+        assert!(!Trick::from_cards([CARD_TAROCK_MOND, CARD_TAROCK_SKIS, CARD_TAROCK_2]).has_trula());
+    }
+
+    #[test]
+    fn all_points_can_be_captured_with_a_full_deck() {
+        let mut remaining: CardSet = CARDS.iter().map(|card| *card).collect();
+        assert_eq!(max_capturable(&remaining), 70);
+        remaining.remove(&CARD_HEARTS_KING);
+        let without_king = max_capturable(&remaining);
+        assert!(without_king < 70);
+        remaining.remove(&CARD_TAROCK_MOND);
+        assert!(max_capturable(&remaining) < without_king);
+        assert_eq!(max_capturable(&HashSet::new()), 0);
+    }
+
+    #[test]
+    fn deal_balance_sums_to_the_points_outside_of_the_talon() {
+        let deal = deal_four_player_standard(&CARDS);
+        let balance = deal_balance(&deal);
+        assert_eq!(balance.len(), 4);
+        let mut talon = Pile::new();
+        for card in deal.talon.cards().iter() {
+            talon.add_card(*card);
+        }
+        assert_eq!(balance.iter().map(|&points| points).sum() as int, MAX_POINTS - talon.score());
+
+        // Every hand of 16 cards leaves a single card that adds no group
+        // point, so the hands are worth a point less than the rest of the deck.
+        let deal = deal_three_player_standard(&CARDS);
+        let balance = deal_balance(&deal);
+        assert_eq!(balance.len(), 3);
+        let mut talon = Pile::new();
+        for card in deal.talon.cards().iter() {
+            talon.add_card(*card);
+        }
+        assert_eq!(balance.iter().map(|&points| points).sum() as int, MAX_POINTS - talon.score() - 1);
+    }
+
+    #[test]
+    fn pile_has_trula_and_kings_collected_from_multiple_tricks() {
+        let mut pile = Pile::new();
+        pile.add_trick(Trick::from_cards([CARD_TAROCK_PAGAT, CARD_HEARTS_KING, CARD_CLUBS_KING, CARD_TAROCK_2]));
+        pile.add_trick(Trick::from_cards([CARD_TAROCK_MOND, CARD_HEARTS_SEVEN, CARD_SPADES_KING, CARD_TAROCK_3]));
+        assert!(!pile.has_trula());
+        assert!(!pile.has_all_kings());
+        pile.add_trick(Trick::from_cards([CARD_TAROCK_SKIS, CARD_DIAMONDS_KING, CARD_CLUBS_SEVEN, CARD_TAROCK_4]));
+        assert!(pile.has_trula());
+        assert!(pile.has_all_kings());
+    }
+
+    #[test]
+    fn spade_king_and_tarocks_beat_the_spade_queen() {
+        let among = set![CARD_SPADES_KING, CARD_SPADES_JACK, CARD_HEARTS_KING,
+                         CARD_TAROCK_PAGAT, CARD_TAROCK_MOND];
+        assert_eq!(cards_beating(&CARD_SPADES_QUEEN, &among),
+                   set![CARD_SPADES_KING, CARD_TAROCK_PAGAT, CARD_TAROCK_MOND]);
+        assert_eq!(cards_beating(&CARD_TAROCK_2, &among), set![CARD_TAROCK_MOND]);
+    }
+
+    #[test]
+    fn cards_of_a_suit_and_tarocks_are_returned_in_order() {
+        let hand = Hand::new([CARD_SPADES_KING, CARD_TAROCK_MOND, CARD_HEARTS_SEVEN, CARD_SPADES_SEVEN,
+                              CARD_TAROCK_PAGAT, CARD_SPADES_JACK, CARD_TAROCK_12]);
+        assert_eq!(hand.cards_of_suit(Spades), vec![CARD_SPADES_SEVEN, CARD_SPADES_JACK, CARD_SPADES_KING]);
+        assert_eq!(hand.cards_of_suit(Diamonds), vec![]);
+        assert_eq!(hand.tarocks(), vec![Tarock1, Tarock12, Tarock21]);
+    }
+
+    #[test]
+    fn trick_is_described_with_players_and_card_symbols() {
+        let trick = Trick::from_cards([CARD_SPADES_KING, CARD_SPADES_SEVEN, CARD_TAROCK_20, CARD_SPADES_QUEEN]);
+        assert_eq!(trick.describe(2, 4).as_slice(), "P2:K♠ P3:7♠ P0:XX P1:Q♠");
+        assert_eq!(CARD_TAROCK_14.symbol().as_slice(), "XIV");
+        assert_eq!(CARD_TAROCK_SKIS.symbol().as_slice(), "Skis");
+    }
+
+    #[test]
+    fn tarocks_are_the_tarock_slice_of_the_deck() {
+        let tarocks = tarocks();
+        assert_eq!(tarocks.len(), 22);
+        assert_eq!(tarocks.as_slice(), CARDS.slice_from(32));
+        assert!(tarocks.iter().all(|card| card.is_tarock()));
+    }
+
+    #[test]
+    fn cards_are_displayed_with_their_names() {
+        assert_eq!(format!("{}", CARD_HEARTS_KING.display()).as_slice(), "King of Hearts");
+        assert_eq!(format!("{}", CARD_DIAMONDS_SEVEN.display()).as_slice(), "Seven of Diamonds");
+        assert_eq!(format!("{}", CARD_TAROCK_PAGAT.display()).as_slice(), "Tarock I (Pagat)");
+        assert_eq!(format!("{}", CARD_TAROCK_14.display()).as_slice(), "Tarock XIV");
+        assert_eq!(format!("{}", CARD_TAROCK_MOND.display()).as_slice(), "Tarock XXI (Mond)");
+        assert_eq!(format!("{}", CARD_TAROCK_SKIS.display()).as_slice(), "Skis");
+        assert_eq!(format!("{}", CARD_HEARTS_KING).as_slice(), "SuitCard(King, Hearts)");
+    }
+
+    #[test]
+    fn cards_are_parsed_from_strings() {
+        assert_eq!(from_str::<Card>("HK"), Some(CARD_HEARTS_KING));
+        assert_eq!(from_str::<Card>("d7"), Some(CARD_DIAMONDS_SEVEN));
+        assert_eq!(from_str::<Card>("T21"), Some(CARD_TAROCK_MOND));
+        assert_eq!(from_str::<Card>("skis"), Some(CARD_TAROCK_SKIS));
+        assert_eq!(from_str::<Card>("T0"), None);
+        assert_eq!(from_str::<Card>("T22"), None);
+        for card in CARDS.iter() {
+            assert_eq!(from_str::<Card>(card.notation().as_slice()), Some(*card));
+        }
+    }
+
+    #[test]
+    fn deck_is_worth_seventy_points() {
+        assert_eq!(deck_point_total(), 70);
+        assert_eq!(deck_point_total() as int, MAX_POINTS);
+    }
+
+    #[test]
+    fn card_index_is_the_position_in_the_deck() {
+        for (i, card) in CARDS.iter().enumerate() {
+            assert_eq!(card.index(), i);
+            assert_eq!(CARDS[card.index()], *card);
+            assert_eq!(Card::from_index(i), Some(*card));
+        }
+        assert_eq!(Card::from_index(NUM_CARDS), None);
+    }
+
+    #[test]
+    fn adding_a_card_to_the_hand_is_idempotent() {
+        let mut hand = Hand::new([CARD_HEARTS_KING]);
+        hand.add_card(CARD_TAROCK_PAGAT);
+        assert_eq!(hand.size(), 2);
+        assert!(hand.has_card(&CARD_TAROCK_PAGAT));
+        hand.add_card(CARD_TAROCK_PAGAT);
+        assert_eq!(hand.size(), 2);
+    }
+
+    #[test]
+    fn hand_without_voids_has_all_suits() {
+        let balanced = Hand::new([CARD_CLUBS_KING, CARD_SPADES_SEVEN, CARD_HEARTS_NINE,
+                                  CARD_DIAMONDS_JACK, CARD_TAROCK_10]);
+        assert!(balanced.has_all_suits());
+        let two_suits = Hand::new([CARD_CLUBS_KING, CARD_CLUBS_SEVEN, CARD_HEARTS_NINE, CARD_TAROCK_10]);
+        assert!(!two_suits.has_all_suits());
+    }
+
+    #[test]
+    fn sorted_cards_of_the_hand_are_in_deck_order() {
+        let hand = Hand::new([CARD_TAROCK_SKIS, CARD_HEARTS_NINE, CARD_CLUBS_KING,
+                              CARD_TAROCK_PAGAT, CARD_HEARTS_SEVEN]);
+        assert_eq!(hand.sorted_cards(), vec![CARD_CLUBS_KING, CARD_HEARTS_SEVEN, CARD_HEARTS_NINE,
+                                             CARD_TAROCK_PAGAT, CARD_TAROCK_SKIS]);
+    }
+
+    #[test]
+    fn talon_is_split_into_parts_of_given_sizes() {
+        let deal = deal_three_player_standard(&CARDS);
+        let parts = deal.talon.split([2, 2, 2]);
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|part| part.len() == 2));
+        assert_eq!(parts[0], deal.talon.cards().slice_to(2));
+        assert_eq!(deal.talon.split([3, 3])[1], deal.talon.cards().slice_from(3));
+    }
+
+    #[test]
+    #[should_fail]
+    fn talon_split_fails_if_the_sizes_do_not_match() {
+        let deal = deal_four_player_standard(&CARDS);
+        deal.talon.split([2, 2]);
+    }
+
+    #[test]
+    fn decks_shuffled_with_the_same_seed_are_equal() {
+        let deck_one = Deck::new().shuffle_seeded(42);
+        let deck_two = Deck::new().shuffle_seeded(42);
+        assert_eq!(deck_one.cards, deck_two.cards);
+        assert_eq!(deck_one.size(), NUM_CARDS);
+    }
+
+    #[test]
+    fn pile_score_is_broken_down_into_groups_of_three() {
+        let mut pile = Pile::new();
+        for &card in [CARD_HEARTS_KING, CARD_HEARTS_QUEEN, CARD_HEARTS_SEVEN,
+                      CARD_CLUBS_SEVEN, CARD_CLUBS_EIGHT, CARD_CLUBS_NINE,
+                      CARD_TAROCK_SKIS].iter() {
+            pile.add_card(card);
+        }
+        let breakdown = pile.score_detailed();
+        assert_eq!(breakdown.groups, vec![8, 1, 4]);
+        assert_eq!(breakdown.total, 13);
+        assert_eq!(pile.score(), breakdown.total);
+        assert_eq!(pile.cards().len(), 7);
+    }
+
+    #[test]
+    fn pile_can_be_scored_in_groups_of_four() {
+        let mut pile = Pile::new();
+        for &card in [CARD_HEARTS_KING, CARD_HEARTS_QUEEN, CARD_HEARTS_JACK, CARD_HEARTS_KNIGHT,
+                      CARD_CLUBS_SEVEN, CARD_CLUBS_EIGHT, CARD_CLUBS_NINE, CARD_CLUBS_TEN].iter() {
+            pile.add_card(card);
+        }
+        assert_eq!(pile.score_with_group_size(4), 12);
+        assert_eq!(pile.score_with_group_size(3), 13);
+        assert_eq!(pile.score(), pile.score_with_group_size(3));
+    }
+
+    #[test]
+    fn current_winner_of_a_partial_trick() {
+        use contracts::standard_winner_strategy;
+
+        let mut trick = Trick::empty();
+        assert!(trick.current_winner(|cards| standard_winner_strategy(cards)).is_none());
+        trick.add_card(CARD_HEARTS_NINE);
+        trick.add_card(CARD_HEARTS_KING);
+        let winner = trick.current_winner(|cards| standard_winner_strategy(cards)).unwrap();
+        assert_eq!(winner.card_index, 1);
+        assert_eq!(winner.card, CARD_HEARTS_KING);
+        trick.add_card(CARD_TAROCK_2);
+        assert_eq!(trick.current_winner(|cards| standard_winner_strategy(cards)).unwrap().card, CARD_TAROCK_2);
+        assert_eq!(trick.count(), 3);
+    }
+
+    #[test]
+    fn equal_deals_have_equal_fingerprints() {
+        let deal = deal_four_player_standard(&CARDS);
+        assert_eq!(deal_fingerprint(&deal), deal_fingerprint(&deal));
+        assert_eq!(deal_fingerprint(&deal), deal_fingerprint(&deal_four_player_standard(&CARDS)));
+        let other = Deck::new().shuffle_seeded(7).deal(deal_four_player_standard);
+        assert!(deal_fingerprint(&deal) != deal_fingerprint(&other));
+    }
+
+    #[test]
+    fn all_suits_and_tarocks_are_listed_in_order() {
+        assert_eq!(ALL_SUITS.len(), 4);
+        assert_eq!(Tarock::all().len(), 22);
+        assert_eq!(Tarock::all()[0], Tarock1);
+        assert_eq!(Tarock::all()[21], TarockSkis);
+        for (i, tarock) in Tarock::all().iter().enumerate() {
+            assert_eq!(tarock.index(), i);
+        }
+    }
 }
@@ -1,9 +1,10 @@
 use std::collections::HashSet;
 
-use bonuses::has_trula;
-use cards::{CardSuit, Trick, Hand, Card, TarockCard, Tarock21, TarockSkis};
+use cards::{CardSuit, Trick, Hand, Card, TarockCard, Tarock21, TarockSkis, sort_cards,
+    SuitCard, King, ALL_SUITS};
+use player::PlayerId;
 
-#[deriving(Eq, PartialEq, Show)]
+#[deriving(Clone, Eq, PartialEq, Show, Encodable, Decodable)]
 pub enum ContractType {
     Three,
     Two,
@@ -11,20 +12,31 @@ pub enum ContractType {
 }
 
 pub mod beggar {
-    #[deriving(Eq, PartialEq, Show)]
+    #[deriving(Clone, Eq, PartialEq, Show, Encodable, Decodable)]
     pub enum Type {
         Normal,
         Open,
     }
 }
 pub mod valat {
-    #[deriving(Eq, PartialEq, Show)]
+    #[deriving(Clone, Eq, PartialEq, Show, Encodable, Decodable)]
     pub enum Type {
         Normal,
         Color,
     }
 }
 
+// How the players are split into sides when playing a contract.
+#[deriving(Clone, Eq, PartialEq, Show)]
+pub enum Roles {
+    // The declarer plays alone against all other players.
+    OneVsRest,
+    // The declarer plays together with the holder of the called king.
+    TwoVsTwo,
+    // Every player plays for himself.
+    AllVsAll,
+}
+
 pub const KLOP: Contract = Klop;
 pub const STANDARD_THREE: Contract = Standard(Three);
 pub const STANDARD_TWO: Contract = Standard(Two);
@@ -38,7 +50,23 @@ pub const BEGGAR_OPEN: Contract = Beggar(beggar::Open);
 pub const VALAT_COLOR: Contract = Valat(valat::Color);
 pub const VALAT_NORMAL: Contract = Valat(valat::Normal);
 
-#[deriving(Eq, PartialEq, Show)]
+// All contracts ordered by their value.
+pub static CONTRACTS: [Contract, ..12] = [
+    KLOP,
+    STANDARD_THREE,
+    STANDARD_TWO,
+    STANDARD_ONE,
+    SOLO_THREE,
+    SOLO_TWO,
+    SOLO_ONE,
+    BEGGAR_NORMAL,
+    SOLO_WITHOUT,
+    BEGGAR_OPEN,
+    VALAT_COLOR,
+    VALAT_NORMAL,
+];
+
+#[deriving(Clone, Eq, PartialEq, Show, Encodable, Decodable)]
 pub enum Contract {
     Klop,
     Standard(ContractType),
@@ -68,6 +96,64 @@ impl Contract {
         }
     }
 
+    // Difference between the score of winning and losing the contract.
+    pub fn swing(&self) -> int {
+        2 * self.value()
+    }
+
+    // Human readable name of the contract.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Klop => "Klop",
+            Standard(Three) => "Three",
+            Standard(Two) => "Two",
+            Standard(One) => "One",
+            Solo(Three) => "Solo Three",
+            Solo(Two) => "Solo Two",
+            Solo(One) => "Solo One",
+            Beggar(beggar::Normal) => "Beggar",
+            SoloWithout => "Solo Without",
+            Beggar(beggar::Open) => "Open Beggar",
+            Valat(valat::Color) => "Color Valat",
+            Valat(valat::Normal) => "Valat",
+        }
+    }
+
+    // Returns true if a bid of the other contract beats this contract. Only a
+    // higher contract beats a bid. The priority of the players never matters
+    // here and the argument is ignored: a contract of the same value bid with
+    // priority only holds it, which is what `held_by` checks.
+    pub fn beaten_by(&self, other: &Contract, _same_priority: bool) -> bool {
+        *other > *self
+    }
+
+    // Returns true if a bid of the other contract holds this contract, that is
+    // a contract of the same value is bid by a player with higher priority.
+    pub fn held_by(&self, other: &Contract, has_priority: bool) -> bool {
+        has_priority && other.value() == self.value()
+    }
+
+    // Rank of the contract in the bidding, its position in `CONTRACTS`. Unlike
+    // the value it orders Klop below all other contracts.
+    pub fn bidding_rank(&self) -> uint {
+        CONTRACTS.iter().position(|contract| contract == self).unwrap()
+    }
+
+    // Compares the contracts by their bidding rank. Contracts of the same value
+    // are still ordered, e.g. Klop is lower than Beggar.
+    pub fn cmp_for_bidding(&self, other: &Contract) -> Ordering {
+        self.bidding_rank().cmp(&other.bidding_rank())
+    }
+
+    // Returns how the players are split into sides for the contract.
+    pub fn player_roles(&self) -> Roles {
+        match *self {
+            Klop => AllVsAll,
+            Standard(_) => TwoVsTwo,
+            _ => OneVsRest,
+        }
+    }
+
     // Returns true if the contract is klop.
     pub fn is_klop(&self) -> bool {
         match *self {
@@ -108,6 +194,54 @@ impl PartialOrd for Contract {
     }
 }
 
+// Returns the suits of kings the declarer may call. Only kings that the
+// declarer does not hold may be called, except when he holds all four kings
+// in which case he may call any of them.
+pub fn callable_kings(declarer_hand: &Hand) -> Vec<CardSuit> {
+    let not_held = ALL_SUITS.iter()
+        .filter(|suit| !declarer_hand.has_card(&SuitCard(King, **suit)))
+        .map(|suit| *suit)
+        .collect::<Vec<CardSuit>>();
+    if not_held.is_empty() {
+        ALL_SUITS.to_vec()
+    } else {
+        not_held
+    }
+}
+
+#[deriving(Clone, Eq, PartialEq, Show)]
+pub enum KingCallError {
+    // The declarer holds the called king and does not hold all four kings.
+    KingInHand,
+}
+
+// Checks that the declarer may call the king. A held king may only be called
+// when the declarer holds all four kings, so with three kings the fourth one
+// has to be called.
+pub fn validate_king_call(declarer_hand: &Hand, king: CardSuit) -> Result<(), KingCallError> {
+    if callable_kings(declarer_hand).contains(&king) {
+        Ok(())
+    } else {
+        Err(KingInHand)
+    }
+}
+
+// Returns the index of the hand holding the called king or `None` if the king
+// is in none of the hands. In the called king variant this is the partner of
+// the declarer.
+pub fn rufer_partner(hands: &[Hand], king: CardSuit) -> Option<PlayerId> {
+    let card = SuitCard(King, king);
+    hands.iter().position(|hand| hand.has_card(&card)).map(|i| i as PlayerId)
+}
+
+// Returns the strategy for finding the winner of a trick in the contract.
+pub fn winner_strategy(contract: &Contract) -> fn(&[Card]) -> uint {
+    match *contract {
+        Valat(valat::Color) => color_valat_winner_strategy,
+        _ => standard_winner_strategy,
+    }
+}
+
 pub fn color_valat_winner_strategy(cards: &[Card]) -> uint {
     find_winner(cards, |card, suit| card.suit() == suit)
 }
@@ -124,7 +258,7 @@ fn find_winner(cards: &[Card], cond: |&Card, Option<CardSuit>| -> bool) ->uint {
         .max_by(|&(_, card)| card)
         .unwrap();
 
-    if card.is_tarock() && has_trula(cards) {
+    if card.is_tarock() && Trick::from_cards(cards).has_trula() {
         let (winner_index, _) = cards.iter()
             .enumerate()
             .find(|&(_, card)| card.is_pagat()).unwrap();
@@ -134,6 +268,15 @@ fn find_winner(cards: &[Card], cond: |&Card, Option<CardSuit>| -> bool) ->uint {
     winner_index
 }
 
+// Returns the validator of played cards for the contract.
+pub fn move_validator(contract: &Contract) -> fn(&Hand, &Trick, &Card) -> bool {
+    match *contract {
+        Klop | Beggar(_) => negative_contract_move_validator,
+        Valat(valat::Color) => color_valat_move_validator,
+        _ => standard_move_validator,
+    }
+}
+
 pub trait MoveValidator {
     fn is_valid(&self, hand: &Hand, trick: &Trick, card: &Card) -> bool;
 }
@@ -172,7 +315,7 @@ pub fn negative_contract_move_validator(hand: &Hand, trick: &Trick, card: &Card)
                 .max_by(|card| card)
                 .unwrap();
             if card.is_pagat() {
-                return contains_mond_and_skis(trick.cards().iter()) || has_only_pagat(hand, card)
+                return contains_mond_and_skis(trick.cards().iter()) || hand.pagat_is_only_tarock()
             } else if card.is_tarock() &&
                 contains_mond_and_skis(trick.cards().iter()) &&
                 hand.cards().filter(|card| card.is_pagat()).count() == 1 {
@@ -185,17 +328,18 @@ pub fn negative_contract_move_validator(hand: &Hand, trick: &Trick, card: &Card)
         } else {
             match card.suit() {
                 Some(_) => !hand.has_tarock(),
-                _ if card.is_pagat() => contains_mond_and_skis(hand.cards()) || has_only_pagat(hand, card),
+                _ if card.is_pagat() => contains_mond_and_skis(hand.cards()) || hand.pagat_is_only_tarock(),
                 _ => suit.map(|suit| !hand.has_suit(&suit)).unwrap_or(true) && !contains_mond_and_skis(trick.cards().iter()),
             }
         }
     }
 }
 
-fn has_only_pagat(hand: &Hand, card: &Card) -> bool {
-    card.is_pagat() && hand.cards().
-        filter(|card| card.is_tarock()).
-        count() == 1
+// Tarocks are not trumps in Color Valat but they lose only their power to win
+// a trick, the cards are played as in the standard contracts: the led suit
+// must be followed if possible and a tarock must be played when void in it.
+pub fn color_valat_move_validator(hand: &Hand, trick: &Trick, card: &Card) -> bool {
+    standard_move_validator(hand, trick, card)
 }
 
 fn contains_mond_and_skis<'a, C: Iterator<&'a Card>>(mut cards: C) -> bool {
@@ -222,12 +366,23 @@ pub fn valid_moves<V: MoveValidator>(validator: V, hand: &Hand, trick: &Trick) -
     hand.cards().filter(|card| validator.is_valid(hand, trick, *card)).map(|c| *c).collect()
 }
 
+// Returns the valid moves sorted in the order of `CARDS`.
+pub fn valid_moves_sorted<V: MoveValidator>(validator: V, hand: &Hand, trick: &Trick) -> Vec<Card> {
+    let mut moves = valid_moves(validator, hand, trick).into_iter().collect::<Vec<Card>>();
+    sort_cards(moves.as_mut_slice());
+    moves
+}
+
 #[cfg(test)]
 mod test {
     use cards::*;
 
-    use super::{standard_winner_strategy, color_valat_winner_strategy};
-    use super::{valid_moves, negative_contract_move_validator, standard_move_validator};
+    use super::{standard_winner_strategy, color_valat_winner_strategy, callable_kings,
+        winner_strategy, rufer_partner, validate_king_call, KingInHand};
+    use super::{Standard, Two, One, Solo, Klop, Valat, valat};
+    use super::{OneVsRest, TwoVsTwo, AllVsAll};
+    use super::{valid_moves, valid_moves_sorted, negative_contract_move_validator, standard_move_validator,
+        color_valat_move_validator, move_validator};
 
     static HIGH_HEARTS_NO_TAROCKS: &'static [Card] = [
         CARD_HEARTS_JACK,
@@ -319,14 +474,6 @@ mod test {
         assert_eq!(color_valat_winner_strategy(TAROCKS_TRULA), 3)
     }
 
-    fn make_trick(cards: &[Card]) -> Trick {
-        let mut trick = Trick::empty();
-        for card in cards.iter() {
-            trick.add_card(*card);
-        }
-        trick
-    }
-
     #[test]
     fn move_validator_all_moves_are_valid_on_first_play_in_trick() {
         let cards = set![CARD_TAROCK_2, CARD_SPADES_EIGHT, CARD_DIAMONDS_JACK];
@@ -341,11 +488,11 @@ mod test {
         let cards = set![CARD_TAROCK_2, CARD_SPADES_EIGHT, CARD_DIAMONDS_JACK];
         assert_eq!(valid_moves(standard_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_SPADES_KING])),
+                               &Trick::from_cards([CARD_SPADES_KING])),
                                set![CARD_SPADES_EIGHT]);
         assert_eq!(valid_moves(negative_contract_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_SPADES_KING])),
+                               &Trick::from_cards([CARD_SPADES_KING])),
                                set![CARD_SPADES_EIGHT]);
     }
 
@@ -354,11 +501,11 @@ mod test {
         let cards = set![CARD_TAROCK_2, CARD_HEARTS_KING, CARD_TAROCK_SKIS, CARD_SPADES_JACK];
         assert_eq!(valid_moves(standard_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_DIAMONDS_KING])),
+                               &Trick::from_cards([CARD_DIAMONDS_KING])),
                                set![CARD_TAROCK_2, CARD_TAROCK_SKIS]);
         assert_eq!(valid_moves(negative_contract_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_DIAMONDS_KING])),
+                               &Trick::from_cards([CARD_DIAMONDS_KING])),
                                set![CARD_TAROCK_2, CARD_TAROCK_SKIS]);
     }
 
@@ -367,11 +514,11 @@ mod test {
         let cards = set![CARD_HEARTS_KING, CARD_DIAMONDS_JACK];
         assert_eq!(valid_moves(standard_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_CLUBS_NINE, CARD_CLUBS_KING])),
+                               &Trick::from_cards([CARD_CLUBS_NINE, CARD_CLUBS_KING])),
                                cards);
         assert_eq!(valid_moves(negative_contract_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_CLUBS_NINE, CARD_CLUBS_KING])),
+                               &Trick::from_cards([CARD_CLUBS_NINE, CARD_CLUBS_KING])),
                                cards);
     }
 
@@ -380,15 +527,15 @@ mod test {
         let cards = set![CARD_TAROCK_13, CARD_SPADES_EIGHT, CARD_SPADES_QUEEN];
         assert_eq!(valid_moves(negative_contract_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_SPADES_KNIGHT, CARD_SPADES_SEVEN])),
+                               &Trick::from_cards([CARD_SPADES_KNIGHT, CARD_SPADES_SEVEN])),
                                set![CARD_SPADES_QUEEN]);
         assert_eq!(valid_moves(negative_contract_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_SPADES_KING, CARD_SPADES_SEVEN])),
+                               &Trick::from_cards([CARD_SPADES_KING, CARD_SPADES_SEVEN])),
                                set![CARD_SPADES_EIGHT, CARD_SPADES_QUEEN]);
         assert_eq!(valid_moves(negative_contract_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_SPADES_KING, CARD_SPADES_SEVEN, CARD_TAROCK_2])),
+                               &Trick::from_cards([CARD_SPADES_KING, CARD_SPADES_SEVEN, CARD_TAROCK_2])),
                                set![CARD_SPADES_EIGHT, CARD_SPADES_QUEEN]);
     }
 
@@ -397,15 +544,15 @@ mod test {
         let cards = set![CARD_TAROCK_13, CARD_HEARTS_JACK, CARD_TAROCK_PAGAT, CARD_TAROCK_5];
         assert_eq!(valid_moves(negative_contract_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_TAROCK_12, CARD_SPADES_SEVEN])),
+                               &Trick::from_cards([CARD_TAROCK_12, CARD_SPADES_SEVEN])),
                                set![CARD_TAROCK_13]);
         assert_eq!(valid_moves(negative_contract_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_TAROCK_SKIS, CARD_SPADES_SEVEN])),
+                               &Trick::from_cards([CARD_TAROCK_SKIS, CARD_SPADES_SEVEN])),
                                set![CARD_TAROCK_13, CARD_TAROCK_5]);
         assert_eq!(valid_moves(negative_contract_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_SPADES_QUEEN])),
+                               &Trick::from_cards([CARD_SPADES_QUEEN])),
                                set![CARD_TAROCK_13, CARD_TAROCK_5]);
     }
 
@@ -414,11 +561,11 @@ mod test {
         let cards = set![CARD_TAROCK_13, CARD_HEARTS_JACK, CARD_TAROCK_PAGAT, CARD_TAROCK_5];
         assert_eq!(valid_moves(negative_contract_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_TAROCK_12, CARD_TAROCK_SKIS, CARD_TAROCK_MOND])),
+                               &Trick::from_cards([CARD_TAROCK_12, CARD_TAROCK_SKIS, CARD_TAROCK_MOND])),
                                set![CARD_TAROCK_PAGAT]);
         assert_eq!(valid_moves(negative_contract_move_validator,
                                &Hand::from_iter(cards.iter()),
-                               &make_trick([CARD_TAROCK_SKIS, CARD_DIAMONDS_JACK, CARD_TAROCK_MOND])),
+                               &Trick::from_cards([CARD_TAROCK_SKIS, CARD_DIAMONDS_JACK, CARD_TAROCK_MOND])),
                                set![CARD_TAROCK_PAGAT]);
     }
 
@@ -427,11 +574,11 @@ mod test {
         let cards = set![CARD_HEARTS_KING, CARD_DIAMONDS_JACK];
         assert_eq!(standard_move_validator(
                 &Hand::from_iter(cards.iter()),
-                &make_trick([CARD_CLUBS_NINE, CARD_CLUBS_KING]),
+                &Trick::from_cards([CARD_CLUBS_NINE, CARD_CLUBS_KING]),
                 &CARD_CLUBS_TEN), false);
         assert_eq!(negative_contract_move_validator(
                 &Hand::from_iter(cards.iter()),
-                &make_trick([CARD_CLUBS_NINE, CARD_CLUBS_KING]),
+                &Trick::from_cards([CARD_CLUBS_NINE, CARD_CLUBS_KING]),
                 &CARD_CLUBS_TEN), false);
     }
 
@@ -440,12 +587,124 @@ mod test {
         let cards = set![CARD_HEARTS_NINE];
         assert_eq!(standard_move_validator(
                 &Hand::from_iter(cards.iter()),
-                &make_trick([CARD_HEARTS_SEVEN]),
+                &Trick::from_cards([CARD_HEARTS_SEVEN]),
                 &CARD_CLUBS_EIGHT), false);
         assert_eq!(negative_contract_move_validator(
                 &Hand::from_iter(cards.iter()),
-                &make_trick([CARD_HEARTS_SEVEN]),
+                &Trick::from_cards([CARD_HEARTS_SEVEN]),
                 &CARD_CLUBS_EIGHT), false);
     }
 
+    #[test]
+    fn kings_not_held_by_the_declarer_can_be_called() {
+        let hand = Hand::new([CARD_CLUBS_KING, CARD_HEARTS_KING, CARD_SPADES_QUEEN, CARD_TAROCK_MOND]);
+        assert_eq!(callable_kings(&hand), vec![Spades, Diamonds]);
+    }
+
+    #[test]
+    fn any_king_can_be_called_when_holding_all_four() {
+        let hand = Hand::new([CARD_CLUBS_KING, CARD_HEARTS_KING, CARD_SPADES_KING, CARD_DIAMONDS_KING]);
+        assert_eq!(callable_kings(&hand), vec![Clubs, Spades, Hearts, Diamonds]);
+    }
+
+    #[test]
+    fn winner_strategy_depends_on_the_contract() {
+        assert_eq!(winner_strategy(&Standard(Two))(SUITS_WITH_TAROCK), 3);
+        assert_eq!(winner_strategy(&Klop)(SUITS_WITH_TAROCK), 3);
+        assert_eq!(winner_strategy(&Valat(valat::Color))(SUITS_WITH_TAROCK), 0);
+    }
+
+    #[test]
+    fn color_valat_tarock_cannot_be_played_if_led_suit_is_held() {
+        let cards = set![CARD_HEARTS_NINE, CARD_TAROCK_10, CARD_CLUBS_KING];
+        assert_eq!(valid_moves(color_valat_move_validator,
+                               &Hand::from_iter(cards.iter()),
+                               &Trick::from_cards([CARD_HEARTS_SEVEN])),
+                               set![CARD_HEARTS_NINE]);
+        assert_eq!(valid_moves(move_validator(&Valat(valat::Color)),
+                               &Hand::from_iter(cards.iter()),
+                               &Trick::from_cards([CARD_HEARTS_SEVEN])),
+                               set![CARD_HEARTS_NINE]);
+    }
+
+    #[test]
+    fn color_valat_tarock_must_be_played_if_void_in_led_suit() {
+        let cards = set![CARD_TAROCK_10, CARD_CLUBS_KING];
+        assert_eq!(valid_moves(color_valat_move_validator,
+                               &Hand::from_iter(cards.iter()),
+                               &Trick::from_cards([CARD_HEARTS_SEVEN])),
+                               set![CARD_TAROCK_10]);
+        let cards = set![CARD_HEARTS_NINE, CARD_TAROCK_10, CARD_CLUBS_KING];
+        assert!(!color_valat_move_validator(&Hand::from_iter(cards.iter()),
+                                            &Trick::from_cards([CARD_HEARTS_SEVEN]),
+                                            &CARD_TAROCK_10));
+        assert_eq!(valid_moves(color_valat_move_validator,
+                               &Hand::from_iter(cards.iter()),
+                               &Trick::from_cards([CARD_TAROCK_2])),
+                               set![CARD_TAROCK_10]);
+    }
+
+    #[test]
+    fn hand_possible_leads_and_responses_are_sorted() {
+        let hand = Hand::new([CARD_TAROCK_13, CARD_SPADES_QUEEN, CARD_HEARTS_JACK, CARD_SPADES_EIGHT]);
+        assert_eq!(hand.possible_leads(),
+                   vec![CARD_SPADES_EIGHT, CARD_SPADES_QUEEN, CARD_HEARTS_JACK, CARD_TAROCK_13]);
+        let trick = Trick::from_cards([CARD_SPADES_KNIGHT]);
+        assert_eq!(hand.possible_responses(&trick, standard_move_validator),
+                   valid_moves_sorted(standard_move_validator, &hand, &trick));
+        assert_eq!(hand.possible_responses(&trick, standard_move_validator),
+                   vec![CARD_SPADES_EIGHT, CARD_SPADES_QUEEN]);
+    }
+
+    #[test]
+    fn higher_contract_beats_a_bid_and_equal_contract_only_holds_it() {
+        let two = Standard(Two);
+        assert!(two.beaten_by(&Standard(One), true));
+        assert!(two.beaten_by(&Standard(One), false));
+        assert!(!two.held_by(&Standard(One), true));
+        assert!(!two.beaten_by(&Standard(Two), true));
+        assert!(!two.beaten_by(&Standard(Two), false));
+        assert!(two.held_by(&Standard(Two), true));
+        assert!(!two.held_by(&Standard(Two), false));
+    }
+
+    #[test]
+    fn rufer_partner_is_the_holder_of_the_called_king() {
+        let hands = [Hand::new([CARD_HEARTS_KING, CARD_TAROCK_2]),
+                     Hand::new([CARD_SPADES_SEVEN, CARD_CLUBS_KING]),
+                     Hand::new([CARD_DIAMONDS_NINE])];
+        assert_eq!(rufer_partner(&hands, Clubs), Some(1));
+        assert_eq!(rufer_partner(&hands, Spades), None);
+    }
+
+    #[test]
+    fn contracts_are_classified_by_player_roles() {
+        assert_eq!(Klop.player_roles(), AllVsAll);
+        assert_eq!(Standard(Two).player_roles(), TwoVsTwo);
+        assert_eq!(Solo(One).player_roles(), OneVsRest);
+    }
+
+    #[test]
+    fn swing_is_twice_the_contract_value() {
+        assert_eq!(Standard(Two).swing(), 40);
+        assert_eq!(Valat(valat::Normal).swing(), 500);
+    }
+
+    #[test]
+    fn fourth_king_can_be_called_with_three_kings() {
+        let hand = Hand::new([CARD_HEARTS_KING, CARD_CLUBS_KING, CARD_SPADES_KING, CARD_TAROCK_2]);
+        assert_eq!(hand.king_count(), 3);
+        assert_eq!(validate_king_call(&hand, Diamonds), Ok(()));
+        assert_eq!(validate_king_call(&hand, Clubs), Err(KingInHand));
+    }
+
+    #[test]
+    fn klop_is_ordered_below_beggar_for_bidding() {
+        use super::{Beggar, beggar};
+
+        assert_eq!(Klop.value(), Beggar(beggar::Normal).value());
+        assert_eq!(Klop.cmp_for_bidding(&Beggar(beggar::Normal)), Less);
+        assert_eq!(Beggar(beggar::Normal).cmp_for_bidding(&Klop), Greater);
+        assert_eq!(Klop.cmp_for_bidding(&Klop), Equal);
+    }
 }
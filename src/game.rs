@@ -1,9 +1,15 @@
+use std::collections::{HashMap, HashSet};
 use std::mem;
+use std::rand::Rng;
 
-use cards::{Card, CardSuit, Trick};
-use contracts::{ContractType, Contract, Standard, standard_winner_strategy,
-    standard_move_validator};
+use bonuses;
+use bonuses::{BonusType, Trula, Kings, KingUltimo, PagatUltimo, BONUS_TYPES};
+
+use cards::{Card, CardSuit, SuitCard, King, Trick, Hand, Pile, CARDS, NUM_CARDS, HALF_POINTS, sort_cards, boss_card,
+    CARD_TAROCK_SKIS, CARD_TAROCK_MOND};
+use contracts::{ContractType, Contract, Standard, winner_strategy, move_validator, valid_moves};
 use player::{Player, PlayerTurn, PlayerId};
+use replay::{Move, SuitConstraints, player_constraints};
 
 #[deriving(Show, PartialEq)]
 pub enum Success {
@@ -16,10 +22,24 @@ pub enum MoveError {
     NotPlayersTurn,
     InvalidCard,
     Done,
+    // The player on turn has no cards although the game is not finished.
+    EmptyHand,
 }
 
 pub type PlayResult = Result<Success, MoveError>;
 
+// Points the declarer predicts to capture before the play starts.
+#[deriving(Clone, Show, Eq, PartialEq)]
+pub struct Prediction(pub int);
+
+impl Prediction {
+    // Returns the predicted number of points.
+    pub fn points(&self) -> int {
+        let Prediction(points) = *self;
+        points
+    }
+}
+
 // The `ContractGame` trait is used to represent a contract game of slovenian tarock.
 pub trait ContractGame {
     // Play a card for the active player.
@@ -34,6 +54,10 @@ pub trait ContractGame {
     // Counting starts at 1. A maximum of 12 tricks can be played in a 4-player game.
     fn trick_number(&self) -> uint;
 
+    // Returns the player that led the current trick. When no card has been
+    // played yet this is the player that will lead it.
+    fn current_leader(&self) -> PlayerId;
+
     // Trtuens true if the last trick was played and the game is finished (no cards left to play).
     fn is_finished(&self) -> bool;
 }
@@ -41,17 +65,25 @@ pub trait ContractGame {
 const NUM_PLAYERS: uint = 4;
 
 // Implementation of `ContractGame` for standard contracts of `Three`, `Two` and `One`.
+// Other contracts can be played with the trick rules of the contract, see
+// `StandardGame::with_contract`.
 pub struct StandardGame<'a> {
     players: &'a mut [Player],
-    // The type of standard contract.
-    contract_type: ContractType,
+    // The contract that is played.
+    contract: Contract,
     // The suit of called king.
     called_king: CardSuit,
     // Current trick.
     trick: Trick,
     turn: PlayerTurn,
+    // Completed tricks together with the player that led them.
+    tricks: Vec<(PlayerId, Trick)>,
     talon: Vec<Card>,
     trick_number: uint,
+    // The player that won the bidding, if known.
+    declarer: Option<PlayerId>,
+    // Points predicted by the declarer, if any.
+    prediction: Option<Prediction>,
     done: bool,
 }
 
@@ -63,20 +95,448 @@ impl<'a> StandardGame<'a> {
                    ty: ContractType,
                    king: CardSuit,
                    talon: Vec<Card>) -> StandardGame<'a> {
+        StandardGame::with_contract(players, Standard(ty), king, talon)
+    }
 
+    // Constructs a new game of any contract. Cards are validated and tricks are
+    // won according to the rules of the contract.
+    pub fn with_contract<'a>(players: &'a mut [Player],
+                             contract: Contract,
+                             king: CardSuit,
+                             talon: Vec<Card>) -> StandardGame<'a> {
         let turn = PlayerTurn::start_with(NUM_PLAYERS, 1);
         StandardGame {
             players: players,
-            contract_type: ty,
+            contract: contract,
             called_king: king,
             trick: Trick::empty(),
             turn: turn,
+            tricks: Vec::new(),
             talon: talon,
             trick_number: 1,
+            declarer: None,
+            prediction: None,
             done: false,
         }
     }
 
+    // Returns the cards played by the player in completed tricks and the current
+    // trick, in the order they were played.
+    pub fn cards_played_by(&self, player: PlayerId) -> Vec<Card> {
+        let mut cards = Vec::new();
+        for &(leader, ref trick) in self.tricks.iter() {
+            match card_played_by(leader, trick, player) {
+                Some(card) => cards.push(card),
+                None => {}
+            }
+        }
+        match card_played_by(*self.turn.started_with(), &self.trick, player) {
+            Some(card) => cards.push(card),
+            None => {}
+        }
+        cards
+    }
+
+    // Returns the number of cards left in the hands of all players.
+    pub fn cards_in_hands(&self) -> uint {
+        self.players.iter().map(|player| player.hand().size()).fold(0, |a, b| a + b)
+    }
+
+    // Returns the led suit if the current player holds cards of it and is
+    // required to follow, `None` if the trick is empty, a tarock was led or
+    // the player is void in the suit.
+    pub fn must_follow(&self) -> Option<CardSuit> {
+        self.trick.first()
+            .and_then(|card| card.suit())
+            .and_then(|suit| if self.current_player().hand().has_suit(&suit) { Some(suit) } else { None })
+    }
+
+    // Returns the suits led in the completed tricks and the current trick,
+    // `None` stands for a tarock lead.
+    pub fn suits_led(&self) -> HashSet<Option<CardSuit>> {
+        self.tricks.iter()
+            .map(|&(_, ref trick)| trick)
+            .chain(Some(&self.trick).into_iter())
+            .filter_map(|trick| trick.first())
+            .map(|card| card.suit())
+            .collect()
+    }
+
+    // Returns true if the skis was already played and the mond was not, which
+    // makes the mond the highest tarock still out.
+    pub fn mond_is_boss(&self) -> bool {
+        let played = |card: &Card| -> bool {
+            self.trick.cards().contains(card) ||
+                self.tricks.iter().any(|&(_, ref trick)| trick.cards().contains(card))
+        };
+        played(&CARD_TAROCK_SKIS) && !played(&CARD_TAROCK_MOND)
+    }
+
+    // Returns true if the hands that still hold cards contain only tarocks.
+    pub fn is_tarock_ending(&self) -> bool {
+        self.players.iter().all(|player| player.hand().cards().all(|card| card.is_tarock()))
+    }
+
+    // Returns the players that led the completed tricks in the order the tricks
+    // were played. Every trick after the first is led by the winner of the
+    // previous trick.
+    pub fn leader_history(&self) -> Vec<PlayerId> {
+        self.tricks.iter().map(|&(leader, _)| leader).collect()
+    }
+
+    // Returns the number of tricks the player is sure to win with the cards
+    // that cannot be beaten: the highest remaining cards of each suit and the
+    // highest remaining tarocks he holds.
+    pub fn sure_tricks(&self, player: PlayerId) -> uint {
+        let played = self.moves().iter().map(|&(_, card)| card).collect::<HashSet<Card>>();
+        let hand = self.players[player as uint].hand();
+        // Suits (tarocks as `None`) in which a higher card is held by someone else.
+        let mut beaten = HashSet::new();
+        let mut sure = 0;
+        for card in CARDS.iter().rev().filter(|card| !played.contains(*card)) {
+            if beaten.contains(&card.suit()) {
+                continue
+            }
+            if hand.has_card(card) {
+                sure += 1;
+            } else {
+                beaten.insert(card.suit());
+            }
+        }
+        sure
+    }
+
+    // Returns true if the pagat was played in the last trick and won it, which
+    // is a bonus in valat. Returns `None` until the game is finished.
+    pub fn pagat_in_last_trick(&self) -> Option<bool> {
+        if !self.is_finished() {
+            return None
+        }
+        let strategy = winner_strategy(&self.contract());
+        self.tricks.last().map(|&(_, ref trick)| {
+            trick.winner(|cards| strategy(cards)).card.is_pagat()
+        })
+    }
+
+    // Returns true if the player on turn has to play the pagat now, because it
+    // is the only valid card in his hand.
+    pub fn pagat_must_be_played(&self) -> bool {
+        let moves = valid_moves(move_validator(&self.contract), self.current_player().hand(), &self.trick);
+        moves.len() == 1 && moves.iter().all(|card| card.is_pagat())
+    }
+
+    // Sets the player that won the bidding and plays the contract.
+    pub fn set_declarer(&mut self, declarer: PlayerId) {
+        self.declarer = Some(declarer);
+    }
+
+    // Returns the declarer or `None` if it was not set.
+    pub fn declarer(&self) -> Option<PlayerId> {
+        self.declarer
+    }
+
+    // Returns the hand of the declarer. Since the game starts after the talon
+    // exchange this is the hand bonuses should be announced with.
+    // Fails if the declarer was not set.
+    pub fn declarer_hand(&self) -> &Hand {
+        match self.declarer {
+            Some(declarer) => self.players[declarer as uint].hand(),
+            None => fail!("the declarer is not set"),
+        }
+    }
+
+    // Returns the number of points the declarer and his partner still need to
+    // capture to win the contract. Negative if they already captured more.
+    // Fails if the declarer was not set.
+    pub fn points_behind(&self) -> int {
+        if self.declarer.is_none() {
+            fail!("the declarer is not set")
+        }
+        let mut pile = Pile::new();
+        for &player in self.declarer_side().iter() {
+            pile.add_pile(self.players[player as uint].pile());
+        }
+        HALF_POINTS + 1 - pile.score()
+    }
+
+    // Returns the cumulative points captured by the declarer side and the
+    // opponents after each completed trick. The tricks taken by each side are
+    // counted together as a single pile.
+    pub fn side_point_timeline(&self) -> Vec<(uint, uint)> {
+        let side = self.declarer_side();
+        let (mut declarer_pile, mut opponent_pile) = (Pile::new(), Pile::new());
+        let mut timeline = Vec::with_capacity(self.tricks.len());
+        for (winner, &(_, ref trick)) in self.trick_taker_history().into_iter().zip(self.tricks.iter()) {
+            if side.contains(&winner) {
+                declarer_pile.add_trick(trick.clone());
+            } else {
+                opponent_pile.add_trick(trick.clone());
+            }
+            timeline.push((declarer_pile.score() as uint, opponent_pile.score() as uint));
+        }
+        timeline
+    }
+
+    // Returns true while the declarer and his partner have won every trick of
+    // a valat contract. Returns false for other contracts.
+    pub fn valat_still_possible(&self) -> bool {
+        let side = self.declarer_side();
+        self.contract.is_valat() && self.trick_taker_history().iter().all(|winner| side.contains(winner))
+    }
+
+    // Returns the bonuses the side of the player can still achieve. Bonuses
+    // are dropped once a card needed for them is captured by the other side,
+    // the called king or the pagat is played before the last trick or a trick
+    // is lost for valat.
+    pub fn achievable_bonuses(&self, player: PlayerId) -> HashSet<BonusType> {
+        let declarer_side = self.declarer_side();
+        let side = if declarer_side.contains(&player) {
+            declarer_side
+        } else {
+            self.players.iter()
+                .map(|p| p.id())
+                .filter(|id| !declarer_side.contains(id))
+                .collect()
+        };
+        let mut achievable = BONUS_TYPES.iter().map(|bonus| *bonus).collect::<HashSet<BonusType>>();
+        let called_king = SuitCard(King, self.called_king);
+        let winners = self.trick_taker_history();
+        for (i, (winner, &(_, ref trick))) in winners.iter().zip(self.tricks.iter()).enumerate() {
+            let won = side.contains(winner);
+            let last = self.done && i == self.tricks.len() - 1;
+            if !won {
+                achievable.remove(&bonuses::Valat);
+            }
+            for card in trick.cards().iter() {
+                if !won && (card.is_pagat() || card.is_mond() || card.is_skis()) {
+                    achievable.remove(&Trula);
+                }
+                if !won && card.is_king() {
+                    achievable.remove(&Kings);
+                }
+                if *card == called_king && (!won || !last) {
+                    achievable.remove(&KingUltimo);
+                }
+                if card.is_pagat() && (!won || !last) {
+                    achievable.remove(&PagatUltimo);
+                }
+            }
+        }
+        achievable
+    }
+
+    // Returns the players that took the completed tricks in order. In Klop the
+    // taker is the player that played the highest card of the led suit or the
+    // highest tarock, the same as in the other contracts, and captures the
+    // cards of the trick.
+    pub fn trick_taker_history(&self) -> Vec<PlayerId> {
+        let strategy = winner_strategy(&self.contract());
+        self.tricks.iter()
+            .map(|&(leader, ref trick)| {
+                next_leader(leader, trick.winner(|cards| strategy(cards)).card_index, NUM_PLAYERS)
+            })
+            .collect()
+    }
+
+    // Returns the declarer and his partner, empty if the declarer is not known.
+    fn declarer_side(&self) -> Vec<PlayerId> {
+        match self.declarer {
+            Some(declarer) => {
+                let mut side = vec![declarer];
+                side.extend(self.players[declarer as uint].partner().into_iter());
+                side
+            }
+            None => Vec::new(),
+        }
+    }
+
+    // Attaches the points predicted by the declarer to the game so they can be
+    // compared to the captured points when scoring.
+    pub fn set_prediction(&mut self, prediction: Prediction) {
+        self.prediction = Some(prediction);
+    }
+
+    // Returns the prediction of the declarer or `None` if none was made.
+    pub fn prediction(&self) -> Option<Prediction> {
+        self.prediction
+    }
+
+    // Returns true if the declarer and his partner captured at least the points
+    // the declarer predicted. Returns `None` if no prediction was made or the
+    // game is not finished yet.
+    pub fn prediction_met(&self) -> Option<bool> {
+        if !self.is_finished() {
+            return None
+        }
+        let mut pile = Pile::new();
+        for &player in self.declarer_side().iter() {
+            pile.add_pile(self.players[player as uint].pile());
+        }
+        self.prediction.map(|prediction| pile.score() >= prediction.points())
+    }
+
+    // Returns true if the card would currently win the trick if it was played.
+    // The card is evaluated against the cards played so far, later cards are
+    // not taken into account.
+    pub fn would_win_trick(&self, card: &Card) -> bool {
+        let mut trick = self.trick.clone();
+        trick.add_card(*card);
+        let strategy = winner_strategy(&self.contract());
+        trick.winner(|cards| strategy(cards)).card_index == trick.count() - 1
+    }
+
+    // Returns the lowest valued valid card of the current player that would
+    // win the trick, lower cards are preferred among the cards of the same
+    // value. Returns `None` if no card can win the trick.
+    pub fn cheapest_winner(&self) -> Option<Card> {
+        let mut winners = valid_moves(move_validator(&self.contract), self.current_player().hand(), &self.trick)
+            .into_iter()
+            .filter(|card| self.would_win_trick(card))
+            .collect::<Vec<Card>>();
+        sort_cards(winners.as_mut_slice());
+        let lowest = winners.iter().map(|card| card.value()).min();
+        lowest.and_then(|value| winners.iter().find(|card| card.value() == value).map(|card| *card))
+    }
+
+    // Returns the valid cards of the current player ordered by a simple
+    // heuristic: when following, the cheapest cards come first; when leading,
+    // the boss cards (highest remaining cards of their suits) come first,
+    // followed by the rest from the cheapest on.
+    pub fn moves_by_priority(&self) -> Vec<Card> {
+        let hand = self.current_player().hand();
+        let mut moves = valid_moves(move_validator(&self.contract), hand, &self.trick)
+            .into_iter()
+            .collect::<Vec<Card>>();
+        sort_cards(moves.as_mut_slice());
+        if self.trick.is_empty() {
+            let played = self.moves().iter().map(|&(_, card)| card).collect::<HashSet<Card>>();
+            let (mut boss, mut rest) = moves.partition(|card| {
+                card.suit().and_then(|suit| boss_card(suit, &played)) == Some(*card)
+            });
+            sort_by_value(rest.as_mut_slice());
+            sort_by_value(boss.as_mut_slice());
+            boss.extend(rest.into_iter());
+            boss
+        } else {
+            sort_by_value(moves.as_mut_slice());
+            moves
+        }
+    }
+
+    // Returns a read-only view of the complete game state including the hands
+    // of all players. Intended for spectators and logging, not for players.
+    pub fn kibitz<'b>(&'b self) -> KibitzView<'b> {
+        KibitzView {
+            players: &*self.players,
+            trick: &self.trick,
+            contract: self.contract(),
+            called_king: self.called_king,
+            current_player: *self.turn.current(),
+            trick_number: self.trick_number,
+        }
+    }
+
+    // Returns the state of the game observable by the player: his own hand,
+    // the public tricks and the points captured so far. Hands of the other
+    // players are not included.
+    pub fn information_set(&self, player: PlayerId) -> InfoSet {
+        InfoSet {
+            player: player,
+            hand: self.players[player as uint].hand().clone(),
+            trick: self.trick.clone(),
+            tricks: self.tricks.clone(),
+            contract: self.contract(),
+            called_king: self.called_king,
+            points: self.players.iter().map(|player| player.pile().score()).collect(),
+        }
+    }
+
+    // Returns a copy of the game as seen by the perspective player: the cards
+    // he cannot see are randomly redistributed among the other players.
+    // Hand sizes are kept and, when possible, cards are only given to players
+    // that are not known to be void in them.
+    // The players of the returned game are stored in `players`.
+    pub fn determinize<'b, R: Rng>(&self,
+                                   players: &'b mut Vec<Player>,
+                                   perspective: PlayerId,
+                                   rng: &mut R) -> StandardGame<'b> {
+        let constraints = player_constraints(self.moves().as_slice(), NUM_PLAYERS);
+        let unseen = self.players.iter()
+            .filter(|player| player.id() != perspective)
+            .flat_map(|player| player.hand().cards())
+            .map(|card| *card)
+            .collect::<Vec<Card>>();
+        let sizes = self.players.iter()
+            .map(|player| if player.id() == perspective { 0 } else { player.hand().size() })
+            .collect::<Vec<uint>>();
+        let hands = deal_unseen(unseen, sizes.as_slice(), &constraints, rng);
+
+        *players = self.players.iter().map(|player| player.clone()).collect();
+        for (player, hand) in players.iter_mut().zip(hands.into_iter()) {
+            if player.id() != perspective {
+                *player.hand_mut() = hand;
+            }
+        }
+        StandardGame {
+            players: players.as_mut_slice(),
+            contract: self.contract,
+            called_king: self.called_king,
+            trick: self.trick.clone(),
+            turn: self.turn.clone(),
+            tricks: self.tricks.clone(),
+            talon: self.talon.clone(),
+            trick_number: self.trick_number,
+            declarer: self.declarer,
+            prediction: self.prediction,
+            done: self.done,
+        }
+    }
+
+    // Checks the consistency of the game state: the game holds the whole deck,
+    // every player holds the same number of cards apart from the card played
+    // to the current trick and the turn belongs to one of the players.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        self.check_invariants_with(NUM_CARDS)
+    }
+
+    // Checks the consistency of the game state like `check_invariants` for a
+    // game that holds the given number of cards instead of the whole deck.
+    pub fn check_invariants_with(&self, expected_cards: uint) -> Result<(), String> {
+        let num_cards = self.talon.len() + self.trick.count() + self.players.iter()
+            .map(|player| player.hand().size() + player.pile().size())
+            .fold(0, |a, b| a + b);
+        if num_cards != expected_cards {
+            return Err(format!("expected {} cards in the game, found {}", expected_cards, num_cards))
+        }
+        let leader = *self.turn.started_with();
+        let mut sizes = self.players.iter().map(|player| {
+            let played = card_played_by(leader, &self.trick, player.id()).is_some();
+            player.hand().size() + if played { 1 } else { 0 }
+        });
+        let size = sizes.next();
+        if !sizes.all(|s| Some(s) == size) {
+            return Err("hand sizes are not consistent with the current trick".to_string())
+        }
+        if self.trick_number != self.tricks.len() + 1 {
+            return Err(format!("trick number {} does not match {} completed tricks",
+                               self.trick_number, self.tricks.len()))
+        }
+        if *self.turn.current() as uint >= self.players.len() || self.turn.num_players() != self.players.len() {
+            return Err(format!("invalid player on turn {}", *self.turn.current()))
+        }
+        Ok(())
+    }
+
+    // Returns all the cards played so far in the order they were played.
+    pub fn moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for &(leader, ref trick) in self.tricks.iter() {
+            moves.extend(trick_moves(leader, trick).into_iter());
+        }
+        moves.extend(trick_moves(*self.turn.started_with(), &self.trick).into_iter());
+        moves
+    }
+
     // Returns a reference to the current active player.
     fn current_player(&self) -> &Player {
         &self.players[*self.turn.current() as uint]
@@ -94,7 +554,9 @@ impl<'a> ContractGame for StandardGame<'a> {
             Err(Done)
         } else if player != *self.turn.current() {
             Err(NotPlayersTurn)
-        } else if !standard_move_validator(self.current_player().hand(), &self.trick, &card) {
+        } else if self.current_player().hand().is_empty() {
+            Err(EmptyHand)
+        } else if !move_validator(&self.contract())(self.current_player().hand(), &self.trick, &card) {
             Err(InvalidCard)
         } else {
             // Remove the played card from the player's hand.
@@ -104,10 +566,13 @@ impl<'a> ContractGame for StandardGame<'a> {
             if self.trick.count() == NUM_PLAYERS {
                 // The trick is finished (all players have played the card).
                 {
-                    let winner = self.trick.winner(standard_winner_strategy);
-                    let player = &mut self.players[to_player_index(&self.turn, winner.card_index)];
+                    let strategy = winner_strategy(&self.contract());
+                    let winner = self.trick.winner(|cards| strategy(cards));
+                    let leader = next_leader(*self.turn.started_with(), winner.card_index, self.turn.num_players());
+                    let player = &mut self.players[leader as uint];
                     // Start with a fresh trick.
                     let trick = mem::replace(&mut self.trick, Trick::empty());
+                    self.tricks.push((*self.turn.started_with(), trick.clone()));
                     // Add the won trick to the player's pile of cards.
                     player.pile_mut().add_trick(trick);
                     // Next active player is the winner of this trick.
@@ -128,31 +593,261 @@ impl<'a> ContractGame for StandardGame<'a> {
     }
 
     fn contract(&self) -> Contract {
-        Standard(self.contract_type)
+        self.contract
     }
 
     fn trick_number(&self) -> uint {
         self.trick_number
     }
 
+    fn current_leader(&self) -> PlayerId {
+        *self.turn.started_with()
+    }
+
     fn is_finished(&self) -> bool {
         self.done
     }
 }
 
-// Convert a winning card index to the player index.
-fn to_player_index(turn: &PlayerTurn, card_index: uint) -> uint {
-    (*turn.started_with() as uint + card_index) % turn.num_players()
+// A read-only snapshot of a game revealing the state of every player.
+pub struct KibitzView<'a> {
+    players: &'a [Player],
+    trick: &'a Trick,
+    contract: Contract,
+    called_king: CardSuit,
+    current_player: PlayerId,
+    trick_number: uint,
+}
+
+impl<'a> KibitzView<'a> {
+    // Returns all players of the game.
+    pub fn players(&self) -> &[Player] {
+        self.players
+    }
+
+    // Returns the current hand of the player with a given id.
+    pub fn hand(&self, player: PlayerId) -> &Hand {
+        self.players[player as uint].hand()
+    }
+
+    // Returns the pile of cards won by the player with a given id.
+    pub fn pile(&self, player: PlayerId) -> &Pile {
+        self.players[player as uint].pile()
+    }
+
+    // Returns the trick that is currently played.
+    pub fn trick(&self) -> &Trick {
+        self.trick
+    }
+
+    // Returns the contract that is played.
+    pub fn contract(&self) -> Contract {
+        self.contract
+    }
+
+    // Returns the suit of the called king.
+    pub fn called_king(&self) -> CardSuit {
+        self.called_king
+    }
+
+    // Returns the player that is currently on turn.
+    pub fn current_player(&self) -> PlayerId {
+        self.current_player
+    }
+
+    // Returns the trick number.
+    pub fn trick_number(&self) -> uint {
+        self.trick_number
+    }
+}
+
+// The state of a game as observed by a single player.
+#[deriving(Show)]
+pub struct InfoSet {
+    player: PlayerId,
+    hand: Hand,
+    trick: Trick,
+    tricks: Vec<(PlayerId, Trick)>,
+    contract: Contract,
+    called_king: CardSuit,
+    points: Vec<int>,
+}
+
+impl InfoSet {
+    // Returns the player observing the game.
+    pub fn player(&self) -> PlayerId {
+        self.player
+    }
+
+    // Returns the hand of the observing player.
+    pub fn hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    // Returns the trick that is currently played.
+    pub fn trick(&self) -> &Trick {
+        &self.trick
+    }
+
+    // Returns the completed tricks together with the player that led them.
+    pub fn tricks(&self) -> &[(PlayerId, Trick)] {
+        self.tricks.as_slice()
+    }
+
+    // Returns the contract that is played.
+    pub fn contract(&self) -> Contract {
+        self.contract
+    }
+
+    // Returns the suit of the called king.
+    pub fn called_king(&self) -> CardSuit {
+        self.called_king
+    }
+
+    // Returns the points captured so far by each player, indexed by player id.
+    pub fn points(&self) -> &[int] {
+        self.points.as_slice()
+    }
+}
+
+// Returns up to `count` cards the declarer should discard after picking up the
+// talon. Kings and tarocks are never discarded, the lowest cards are preferred
+// and among cards of the same value the ones from shorter suits so the suits
+// can be cleared.
+pub fn recommend_discards(hand: &Hand, count: uint) -> Vec<Card> {
+    let mut discards = hand.cards()
+        .filter(|card| !card.is_tarock() && !card.is_king())
+        .map(|card| (card.value(), suit_length(hand, card), *card))
+        .collect::<Vec<(uint, uint, Card)>>();
+    discards.sort_by(|&(value, length, _), &(other_value, other_length, _)| {
+        (value, length).cmp(&(other_value, other_length))
+    });
+    discards.iter().take(count).map(|&(_, _, card)| card).collect()
+}
+
+// Returns all combinations of `count` cards the declarer may discard after
+// picking up the talon. Kings and tarocks may not be discarded.
+// At most three cards can be discarded.
+pub fn legal_discard_sets(hand_after_pickup: &Hand, count: uint) -> Vec<Vec<Card>> {
+    assert!(count <= 3);
+    let mut cards = hand_after_pickup.cards()
+        .filter(|card| !card.is_tarock() && !card.is_king())
+        .map(|card| *card)
+        .collect::<Vec<Card>>();
+    sort_cards(cards.as_mut_slice());
+    combinations(cards.as_slice(), count)
+}
+
+// Returns all combinations of `count` cards, keeping the order of the cards.
+fn combinations(cards: &[Card], count: uint) -> Vec<Vec<Card>> {
+    if count == 0 {
+        return vec![vec![]]
+    }
+    let mut result = Vec::new();
+    for (i, card) in cards.iter().enumerate() {
+        for rest in combinations(cards.slice_from(i + 1), count - 1).into_iter() {
+            let mut combination = vec![*card];
+            combination.extend(rest.into_iter());
+            result.push(combination);
+        }
+    }
+    result
+}
+
+// Sorts the cards by their value keeping the order of cards of the same value,
+// the sort is stable.
+fn sort_by_value(cards: &mut [Card]) {
+    cards.sort_by(|a, b| a.value().cmp(&b.value()));
+}
+
+// Returns the number of cards in the hand of the same suit as the card.
+fn suit_length(hand: &Hand, card: &Card) -> uint {
+    hand.cards().filter(|c| c.suit() == card.suit()).count()
+}
+
+// Returns the player that leads the next trick: the winner of the trick led by
+// `leader`, given the index of the winning card in the trick.
+pub fn next_leader(leader: PlayerId, winner_card_index: uint, num_players: uint) -> PlayerId {
+    ((leader as uint + winner_card_index) % num_players) as PlayerId
+}
+
+// Returns the cards of a trick led by the leader together with the players
+// that played them.
+fn trick_moves(leader: PlayerId, trick: &Trick) -> Vec<Move> {
+    trick.cards().iter()
+        .enumerate()
+        .map(|(i, card)| ((leader + i as PlayerId) % NUM_PLAYERS as PlayerId, *card))
+        .collect()
+}
+
+// Randomly deals the cards into hands of the given sizes. Players are only dealt
+// cards they may hold according to the constraints unless no such deal is found
+// after a number of attempts.
+fn deal_unseen<R: Rng>(mut cards: Vec<Card>,
+                       sizes: &[uint],
+                       constraints: &HashMap<PlayerId, SuitConstraints>,
+                       rng: &mut R) -> Vec<Hand> {
+    const MAX_ATTEMPTS: uint = 100;
+    for attempt in range(0, MAX_ATTEMPTS + 1) {
+        let constrained = attempt < MAX_ATTEMPTS;
+        rng.shuffle(cards.as_mut_slice());
+        if constrained {
+            // Deal the cards that can be held by the fewest players first.
+            cards.sort_by(|a, b| {
+                num_holders(a, sizes, constraints).cmp(&num_holders(b, sizes, constraints))
+            });
+        }
+        let mut remaining = sizes.to_vec();
+        let mut hands = Vec::from_fn(sizes.len(), |_| Vec::new());
+        let mut dealt = true;
+        for card in cards.iter() {
+            let candidates = range(0, sizes.len())
+                .filter(|&i| remaining[i] > 0 && (!constrained || may_hold(i, card, constraints)))
+                .collect::<Vec<uint>>();
+            if candidates.is_empty() {
+                dealt = false;
+                break
+            }
+            let i = candidates[rng.gen_range(0, candidates.len())];
+            *remaining.get_mut(i) -= 1;
+            hands.get_mut(i).push(*card);
+        }
+        if dealt {
+            return hands.iter().map(|cards| Hand::new(cards.as_slice())).collect()
+        }
+    }
+    unreachable!()
+}
+
+// Returns the number of players with free space in their hand that may hold the card.
+fn num_holders(card: &Card, sizes: &[uint], constraints: &HashMap<PlayerId, SuitConstraints>) -> uint {
+    range(0, sizes.len())
+        .filter(|&i| sizes[i] > 0 && may_hold(i, card, constraints))
+        .count()
+}
+
+// Returns true if the player may hold the card according to the constraints.
+fn may_hold(player: uint, card: &Card, constraints: &HashMap<PlayerId, SuitConstraints>) -> bool {
+    constraints.find(&(player as PlayerId))
+        .map(|constraints| constraints.may_hold(card))
+        .unwrap_or(true)
+}
+
+// Returns the card the player played in a trick led by the leader or `None` if
+// the player did not play a card in the trick yet.
+fn card_played_by(leader: PlayerId, trick: &Trick, player: PlayerId) -> Option<Card> {
+    let card_index = (player as uint + NUM_PLAYERS - leader as uint) % NUM_PLAYERS;
+    trick.cards().get(card_index).map(|card| *card)
 }
 
 #[cfg(test)]
 mod test {
     use cards::*;
-    use contracts::{Three, Standard};
+    use contracts::{Three, Standard, KLOP, BEGGAR_NORMAL, VALAT_NORMAL};
     use player::Player;
 
     use super::{StandardGame, ContractGame, NotPlayersTurn, Next, InvalidCard,
-        Done, Last};
+        Done, EmptyHand, Last, Prediction};
 
     fn players() -> Vec<Player> {
         vec![
@@ -250,4 +945,621 @@ mod test {
         assert!(game.is_finished());
         assert_eq!(game.play_card(3, CARD_DIAMONDS_EIGHT), Err(Done));
     }
+
+    #[test]
+    fn cards_played_by_a_player_are_returned_in_order() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT, CARD_SPADES_EIGHT, CARD_CLUBS_EIGHT, CARD_DIAMONDS_EIGHT])),
+            Player::new(1, Hand::new([CARD_HEARTS_KING, CARD_SPADES_KING, CARD_CLUBS_KING, CARD_DIAMONDS_KING])),
+            Player::new(2, Hand::new([CARD_HEARTS_SEVEN, CARD_SPADES_SEVEN, CARD_CLUBS_SEVEN, CARD_DIAMONDS_SEVEN])),
+            Player::new(3, Hand::new([CARD_HEARTS_NINE, CARD_SPADES_NINE, CARD_CLUBS_NINE, CARD_DIAMONDS_NINE])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        for &(king, seven, nine, eight) in [
+                (CARD_HEARTS_KING, CARD_HEARTS_SEVEN, CARD_HEARTS_NINE, CARD_HEARTS_EIGHT),
+                (CARD_SPADES_KING, CARD_SPADES_SEVEN, CARD_SPADES_NINE, CARD_SPADES_EIGHT),
+                (CARD_CLUBS_KING, CARD_CLUBS_SEVEN, CARD_CLUBS_NINE, CARD_CLUBS_EIGHT)].iter() {
+            assert_eq!(game.play_card(1, king), Ok(Next(2)));
+            assert_eq!(game.play_card(2, seven), Ok(Next(3)));
+            assert_eq!(game.play_card(3, nine), Ok(Next(0)));
+            assert_eq!(game.play_card(0, eight), Ok(Next(1)));
+        }
+        assert_eq!(game.play_card(1, CARD_DIAMONDS_KING), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_DIAMONDS_SEVEN), Ok(Next(3)));
+        assert_eq!(game.cards_played_by(3), vec![CARD_HEARTS_NINE, CARD_SPADES_NINE, CARD_CLUBS_NINE]);
+        assert_eq!(game.cards_played_by(2),
+                   vec![CARD_HEARTS_SEVEN, CARD_SPADES_SEVEN, CARD_CLUBS_SEVEN, CARD_DIAMONDS_SEVEN]);
+    }
+
+    #[test]
+    fn kibitz_view_reveals_all_hands() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_TAROCK_SKIS, CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_TAROCK_10, CARD_HEARTS_NINE])),
+            Player::new(2, Hand::new([CARD_HEARTS_JACK, CARD_CLUBS_EIGHT])),
+            Player::new(3, Hand::new([CARD_TAROCK_MOND, CARD_SPADES_JACK])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.play_card(1, CARD_HEARTS_NINE), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_HEARTS_JACK), Ok(Next(3)));
+        let view = game.kibitz();
+        let num_cards = view.players().iter().map(|p| view.hand(p.id()).size()).fold(0, |a, b| a + b);
+        assert_eq!(num_cards, 6);
+        assert!(view.hand(0).has_card(&CARD_TAROCK_SKIS));
+        assert_eq!(view.trick().cards(), [CARD_HEARTS_NINE, CARD_HEARTS_JACK].as_slice());
+        assert_eq!(view.current_player(), 3);
+        assert_eq!(view.contract(), Standard(Three));
+    }
+
+    #[test]
+    fn prediction_can_be_attached_to_the_game() {
+        let mut players = players();
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.prediction(), None);
+        game.set_prediction(Prediction(45));
+        assert_eq!(game.prediction(), Some(Prediction(45)));
+        assert_eq!(game.prediction().unwrap().points(), 45);
+    }
+
+    #[test]
+    fn tarock_would_win_a_trick_of_led_suit() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_HEARTS_NINE])),
+            Player::new(2, Hand::new([CARD_TAROCK_2, CARD_HEARTS_SEVEN])),
+            Player::new(3, Hand::new([CARD_HEARTS_KING])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert!(game.would_win_trick(&CARD_HEARTS_SEVEN));
+        assert_eq!(game.play_card(1, CARD_HEARTS_NINE), Ok(Next(2)));
+        assert!(game.would_win_trick(&CARD_TAROCK_2));
+        assert!(!game.would_win_trick(&CARD_HEARTS_SEVEN));
+    }
+
+    #[test]
+    fn determinized_game_keeps_the_perspective_hand_and_hand_sizes() {
+        use std::collections::HashSet;
+        use std::rand::task_rng;
+
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_CLUBS_EIGHT, CARD_TAROCK_2, CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_CLUBS_KING, CARD_TAROCK_3, CARD_HEARTS_KING])),
+            Player::new(2, Hand::new([CARD_CLUBS_SEVEN, CARD_TAROCK_4, CARD_HEARTS_SEVEN])),
+            Player::new(3, Hand::new([CARD_SPADES_NINE, CARD_TAROCK_5, CARD_HEARTS_NINE])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.play_card(1, CARD_CLUBS_KING), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_CLUBS_SEVEN), Ok(Next(3)));
+        assert_eq!(game.play_card(3, CARD_TAROCK_5), Ok(Next(0)));
+        assert_eq!(game.play_card(0, CARD_CLUBS_EIGHT), Ok(Next(3)));
+
+        let mut determinized_players = Vec::new();
+        let determinized = game.determinize(&mut determinized_players, 0, &mut task_rng());
+        let original = game.kibitz();
+        let view = determinized.kibitz();
+        assert_eq!(view.hand(0), original.hand(0));
+        let mut unseen = HashSet::new();
+        let mut determinized_unseen = HashSet::new();
+        for id in range(1u64, 4) {
+            assert_eq!(view.hand(id).size(), 2);
+            unseen.extend(original.hand(id).cards().map(|c| *c));
+            determinized_unseen.extend(view.hand(id).cards().map(|c| *c));
+        }
+        assert_eq!(unseen, determinized_unseen);
+        assert_eq!(view.current_player(), 3);
+        assert_eq!(view.trick_number(), 2);
+    }
+
+    #[test]
+    fn information_set_excludes_the_hands_of_other_players() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_TAROCK_SKIS, CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_TAROCK_10, CARD_HEARTS_NINE])),
+            Player::new(2, Hand::new([CARD_HEARTS_JACK, CARD_CLUBS_EIGHT])),
+            Player::new(3, Hand::new([CARD_TAROCK_MOND, CARD_SPADES_JACK])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.play_card(1, CARD_HEARTS_NINE), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_HEARTS_JACK), Ok(Next(3)));
+        assert_eq!(game.play_card(3, CARD_TAROCK_MOND), Ok(Next(0)));
+        assert_eq!(game.play_card(0, CARD_HEARTS_EIGHT), Ok(Next(3)));
+        assert_eq!(game.play_card(3, CARD_SPADES_JACK), Ok(Next(0)));
+        let info = game.information_set(0);
+        assert_eq!(info.player(), 0);
+        assert_eq!(*info.hand(), Hand::new([CARD_TAROCK_SKIS]));
+        assert_eq!(info.trick().cards(), [CARD_SPADES_JACK].as_slice());
+        assert_eq!(info.tricks().len(), 1);
+        assert_eq!(info.tricks()[0],
+                   (1, Trick::from_cards([CARD_HEARTS_NINE, CARD_HEARTS_JACK, CARD_TAROCK_MOND, CARD_HEARTS_EIGHT])));
+        assert_eq!(info.contract(), Standard(Three));
+        assert_eq!(info.called_king(), Hearts);
+        assert!(info.points()[3] > 0);
+        assert_eq!(info.points()[0], 0);
+    }
+
+    #[test]
+    fn leaders_of_completed_tricks_are_the_previous_winners() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT, CARD_SPADES_KING, CARD_CLUBS_EIGHT])),
+            Player::new(1, Hand::new([CARD_HEARTS_KING, CARD_SPADES_EIGHT, CARD_CLUBS_NINE])),
+            Player::new(2, Hand::new([CARD_HEARTS_SEVEN, CARD_SPADES_SEVEN, CARD_CLUBS_KING])),
+            Player::new(3, Hand::new([CARD_HEARTS_NINE, CARD_SPADES_NINE, CARD_CLUBS_SEVEN])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.leader_history(), vec![]);
+        assert_eq!(game.play_card(1, CARD_HEARTS_KING), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_HEARTS_SEVEN), Ok(Next(3)));
+        assert_eq!(game.play_card(3, CARD_HEARTS_NINE), Ok(Next(0)));
+        assert_eq!(game.play_card(0, CARD_HEARTS_EIGHT), Ok(Next(1)));
+        assert_eq!(game.play_card(1, CARD_SPADES_EIGHT), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_SPADES_SEVEN), Ok(Next(3)));
+        assert_eq!(game.play_card(3, CARD_SPADES_NINE), Ok(Next(0)));
+        assert_eq!(game.play_card(0, CARD_SPADES_KING), Ok(Next(0)));
+        assert_eq!(game.play_card(0, CARD_CLUBS_EIGHT), Ok(Next(1)));
+        assert_eq!(game.play_card(1, CARD_CLUBS_NINE), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_CLUBS_KING), Ok(Next(3)));
+        assert_eq!(game.play_card(3, CARD_CLUBS_SEVEN), Ok(Last));
+        assert_eq!(game.leader_history(), vec![1, 1, 0]);
+    }
+
+    #[test]
+    fn invariants_hold_during_the_game() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT, CARD_SPADES_KING])),
+            Player::new(1, Hand::new([CARD_HEARTS_KING, CARD_SPADES_EIGHT])),
+            Player::new(2, Hand::new([CARD_HEARTS_SEVEN, CARD_SPADES_SEVEN])),
+            Player::new(3, Hand::new([CARD_HEARTS_NINE, CARD_SPADES_NINE])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![CARD_TAROCK_2]);
+        assert_eq!(game.check_invariants_with(9), Ok(()));
+        assert!(game.check_invariants().is_err());
+        for &(player, card) in [(1, CARD_HEARTS_KING), (2, CARD_HEARTS_SEVEN), (3, CARD_HEARTS_NINE),
+                                (0, CARD_HEARTS_EIGHT), (1, CARD_SPADES_EIGHT), (2, CARD_SPADES_SEVEN)].iter() {
+            assert!(game.play_card(player, card).is_ok());
+            assert_eq!(game.check_invariants_with(9), Ok(()));
+        }
+    }
+
+    #[test]
+    fn invariants_fail_if_a_card_is_missing_from_the_deal() {
+        let deal = deal_four_player_standard(&CARDS);
+        let talon = deal.talon.cards().to_vec();
+        let mut players = deal.hands.into_iter()
+            .enumerate()
+            .map(|(id, hand)| Player::new(id as u64, hand))
+            .collect::<Vec<Player>>();
+        {
+            let game = StandardGame::new(players.as_mut_slice(), Three, Hearts, talon.clone());
+            assert_eq!(game.check_invariants(), Ok(()));
+        }
+        let card = *players[0].hand().cards().next().unwrap();
+        players.get_mut(0).hand_mut().remove_card(&card);
+        let game = StandardGame::new(players.as_mut_slice(), Three, Hearts, talon);
+        assert!(game.check_invariants().is_err());
+    }
+
+    #[test]
+    fn holding_mond_and_skis_gives_sure_tricks() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_TAROCK_SKIS, CARD_TAROCK_MOND, CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_TAROCK_10, CARD_HEARTS_NINE, CARD_CLUBS_SEVEN])),
+            Player::new(2, Hand::new([CARD_HEARTS_KING, CARD_CLUBS_EIGHT, CARD_TAROCK_2])),
+            Player::new(3, Hand::new([CARD_TAROCK_20, CARD_SPADES_JACK, CARD_HEARTS_SEVEN])),
+        ];
+        let game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert!(game.sure_tricks(0) >= 2);
+        assert_eq!(game.sure_tricks(0), 2);
+        assert_eq!(game.sure_tricks(2), 1);
+        assert_eq!(game.sure_tricks(1), 0);
+    }
+
+    #[test]
+    fn lowest_cards_of_short_suits_are_recommended_for_discard() {
+        use super::recommend_discards;
+
+        let hand = Hand::new([CARD_HEARTS_KING, CARD_TAROCK_2, CARD_CLUBS_SEVEN, CARD_CLUBS_EIGHT,
+                              CARD_CLUBS_QUEEN, CARD_SPADES_NINE, CARD_DIAMONDS_JACK]);
+        let discards = recommend_discards(&hand, 3);
+        assert_eq!(discards.len(), 3);
+        assert_eq!(discards[0], CARD_SPADES_NINE);
+        assert!(discards.contains(&CARD_CLUBS_SEVEN));
+        assert!(discards.contains(&CARD_CLUBS_EIGHT));
+        assert_eq!(recommend_discards(&hand, 10).len(), 5);
+    }
+
+    #[test]
+    fn points_behind_are_counted_for_the_declarer_and_his_partner() {
+        let mut players = players();
+        for &card in [CARD_HEARTS_KING, CARD_HEARTS_SEVEN, CARD_HEARTS_EIGHT,
+                      CARD_SPADES_KING, CARD_SPADES_SEVEN, CARD_SPADES_EIGHT].iter() {
+            players.get_mut(1).pile_mut().add_card(card);
+        }
+        for &card in [CARD_CLUBS_KING, CARD_CLUBS_SEVEN, CARD_CLUBS_EIGHT,
+                      CARD_DIAMONDS_KING, CARD_DIAMONDS_SEVEN, CARD_DIAMONDS_EIGHT].iter() {
+            players.get_mut(3).pile_mut().add_card(card);
+        }
+        players.get_mut(1).set_partner(3);
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        game.set_declarer(1);
+        assert_eq!(game.declarer(), Some(1));
+        assert_eq!(game.points_behind(), 16);
+    }
+
+    #[test]
+    fn winner_of_the_trick_leads_next() {
+        use super::next_leader;
+
+        assert_eq!(next_leader(1, 0, 4), 1);
+        assert_eq!(next_leader(1, 3, 4), 0);
+        assert_eq!(next_leader(3, 2, 4), 1);
+        assert_eq!(next_leader(2, 2, 3), 1);
+    }
+
+    #[test]
+    fn pagat_winning_the_last_trick_is_detected() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_NINE])),
+            Player::new(1, Hand::new([CARD_TAROCK_PAGAT])),
+            Player::new(2, Hand::new([CARD_HEARTS_SEVEN])),
+            Player::new(3, Hand::new([CARD_HEARTS_EIGHT])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.play_card(1, CARD_TAROCK_PAGAT), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_HEARTS_SEVEN), Ok(Next(3)));
+        assert_eq!(game.play_card(3, CARD_HEARTS_EIGHT), Ok(Next(0)));
+        assert_eq!(game.pagat_in_last_trick(), None);
+        assert_eq!(game.play_card(0, CARD_HEARTS_NINE), Ok(Last));
+        assert_eq!(game.pagat_in_last_trick(), Some(true));
+    }
+
+    #[test]
+    fn all_combinations_of_legal_discards_are_returned() {
+        use super::legal_discard_sets;
+
+        let hand = Hand::new([CARD_HEARTS_KING, CARD_TAROCK_2, CARD_CLUBS_SEVEN, CARD_CLUBS_EIGHT,
+                              CARD_SPADES_NINE, CARD_DIAMONDS_JACK]);
+        assert_eq!(legal_discard_sets(&hand, 0).len(), 1);
+        assert_eq!(legal_discard_sets(&hand, 1).len(), 4);
+        assert_eq!(legal_discard_sets(&hand, 2).len(), 6);
+        let sets = legal_discard_sets(&hand, 3);
+        assert_eq!(sets.len(), 4);
+        assert_eq!(sets[0], vec![CARD_CLUBS_SEVEN, CARD_CLUBS_EIGHT, CARD_SPADES_NINE]);
+    }
+
+    #[test]
+    fn side_points_are_accumulated_after_each_trick() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT, CARD_SPADES_KING, CARD_CLUBS_EIGHT, CARD_DIAMONDS_EIGHT])),
+            Player::new(1, Hand::new([CARD_HEARTS_KING, CARD_SPADES_EIGHT, CARD_CLUBS_NINE, CARD_DIAMONDS_KING])),
+            Player::new(2, Hand::new([CARD_HEARTS_SEVEN, CARD_SPADES_SEVEN, CARD_CLUBS_SEVEN, CARD_DIAMONDS_NINE])),
+            Player::new(3, Hand::new([CARD_HEARTS_NINE, CARD_SPADES_NINE, CARD_CLUBS_KING, CARD_DIAMONDS_SEVEN])),
+        ];
+        players.get_mut(1).set_partner(3);
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        game.set_declarer(1);
+        // The declarer side takes the first, the third and the last trick.
+        for &(player, card) in [(1, CARD_HEARTS_KING), (2, CARD_HEARTS_SEVEN), (3, CARD_HEARTS_NINE),
+                                (0, CARD_HEARTS_EIGHT), (1, CARD_SPADES_EIGHT), (2, CARD_SPADES_SEVEN),
+                                (3, CARD_SPADES_NINE), (0, CARD_SPADES_KING), (0, CARD_CLUBS_EIGHT),
+                                (1, CARD_CLUBS_NINE), (2, CARD_CLUBS_SEVEN), (3, CARD_CLUBS_KING),
+                                (3, CARD_DIAMONDS_SEVEN), (0, CARD_DIAMONDS_EIGHT), (1, CARD_DIAMONDS_KING),
+                                (2, CARD_DIAMONDS_NINE)].iter() {
+            assert!(game.play_card(player, card).is_ok());
+        }
+        let timeline = game.side_point_timeline();
+        assert_eq!(timeline.len(), 4);
+        for window in timeline.as_slice().windows(2) {
+            let ((declarer, opponents), (next_declarer, next_opponents)) = (window[0], window[1]);
+            assert!(declarer <= next_declarer && opponents <= next_opponents);
+        }
+        let view = game.kibitz();
+        let (mut declarer_pile, mut opponent_pile) = (Pile::new(), Pile::new());
+        declarer_pile.add_pile(view.pile(1));
+        declarer_pile.add_pile(view.pile(3));
+        opponent_pile.add_pile(view.pile(0));
+        opponent_pile.add_pile(view.pile(2));
+        assert_eq!(timeline[3], (declarer_pile.score() as uint, opponent_pile.score() as uint));
+    }
+
+    #[test]
+    fn playing_from_an_empty_hand_of_an_unfinished_game_is_an_error() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_HEARTS_NINE])),
+            Player::new(2, Hand::empty()),
+            Player::new(3, Hand::new([CARD_HEARTS_KING])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.play_card(1, CARD_HEARTS_NINE), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_HEARTS_SEVEN), Err(EmptyHand));
+        assert!(!game.is_finished());
+    }
+
+    #[test]
+    fn pagat_must_be_played_on_trula_in_beggar() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_KING, CARD_HEARTS_QUEEN])),
+            Player::new(1, Hand::new([CARD_TAROCK_MOND, CARD_HEARTS_EIGHT])),
+            Player::new(2, Hand::new([CARD_TAROCK_SKIS, CARD_HEARTS_NINE])),
+            Player::new(3, Hand::new([CARD_TAROCK_PAGAT, CARD_TAROCK_5, CARD_HEARTS_SEVEN])),
+        ];
+        let mut game = StandardGame::with_contract(players.as_mut_slice(), BEGGAR_NORMAL, Hearts, vec![]);
+        assert_eq!(game.contract(), BEGGAR_NORMAL);
+        assert!(!game.pagat_must_be_played());
+        assert_eq!(game.play_card(1, CARD_TAROCK_MOND), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_TAROCK_SKIS), Ok(Next(3)));
+        assert!(game.pagat_must_be_played());
+        assert_eq!(game.play_card(3, CARD_TAROCK_5), Err(InvalidCard));
+        assert_eq!(game.play_card(3, CARD_TAROCK_PAGAT), Ok(Next(0)));
+    }
+
+    #[test]
+    fn cards_in_hands_decrease_after_each_trick() {
+        use contracts::standard_move_validator;
+
+        let deal = deal_four_player_standard(&CARDS);
+        let mut players = deal.hands.into_iter()
+            .enumerate()
+            .map(|(id, hand)| Player::new(id as u64, hand))
+            .collect::<Vec<Player>>();
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.cards_in_hands(), 48);
+        for trick in range(1u, 3) {
+            for _ in range(0u, 4) {
+                let (player, card) = {
+                    let view = game.kibitz();
+                    let player = view.current_player();
+                    (player, view.hand(player).possible_responses(view.trick(), standard_move_validator)[0])
+                };
+                assert!(game.play_card(player, card).is_ok());
+            }
+            assert_eq!(game.cards_in_hands(), 48 - 4 * trick);
+        }
+    }
+
+    #[test]
+    fn lowest_tarock_is_the_cheapest_winner_of_a_suit_trick() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_HEARTS_KING])),
+            Player::new(2, Hand::new([CARD_TAROCK_20, CARD_TAROCK_3, CARD_TAROCK_MOND, CARD_CLUBS_SEVEN])),
+            Player::new(3, Hand::new([CARD_HEARTS_SEVEN])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.play_card(1, CARD_HEARTS_KING), Ok(Next(2)));
+        assert_eq!(game.cheapest_winner(), Some(CARD_TAROCK_3));
+        assert_eq!(game.play_card(2, CARD_TAROCK_3), Ok(Next(3)));
+        assert_eq!(game.cheapest_winner(), None);
+    }
+
+    #[test]
+    fn only_tarocks_left_in_hands_is_a_tarock_ending() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_TAROCK_2, CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_HEARTS_NINE])),
+            Player::new(2, Hand::new([CARD_TAROCK_3, CARD_TAROCK_4])),
+            Player::new(3, Hand::new([CARD_TAROCK_5, CARD_TAROCK_6])),
+        ];
+        {
+            let game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+            assert!(!game.is_tarock_ending());
+        }
+        *players.get_mut(0).hand_mut() = Hand::new([CARD_TAROCK_2]);
+        *players.get_mut(1).hand_mut() = Hand::empty();
+        let game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert!(game.is_tarock_ending());
+    }
+
+    #[test]
+    fn player_holding_the_led_suit_must_follow() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_SPADES_KING])),
+            Player::new(2, Hand::new([CARD_SPADES_SEVEN])),
+            Player::new(3, Hand::new([CARD_TAROCK_2])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.must_follow(), None);
+        assert_eq!(game.play_card(1, CARD_SPADES_KING), Ok(Next(2)));
+        assert_eq!(game.must_follow(), Some(Spades));
+        assert_eq!(game.play_card(2, CARD_SPADES_SEVEN), Ok(Next(3)));
+        assert_eq!(game.must_follow(), None);
+    }
+
+    #[test]
+    fn boss_cards_are_led_first_and_cheap_cards_follow() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_CLUBS_SEVEN, CARD_HEARTS_KING, CARD_SPADES_QUEEN, CARD_SPADES_EIGHT])),
+            Player::new(2, Hand::new([CARD_HEARTS_SEVEN, CARD_HEARTS_QUEEN, CARD_HEARTS_NINE])),
+            Player::new(3, Hand::new([CARD_TAROCK_2])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.moves_by_priority(),
+                   vec![CARD_HEARTS_KING, CARD_CLUBS_SEVEN, CARD_SPADES_EIGHT, CARD_SPADES_QUEEN]);
+        assert_eq!(game.play_card(1, CARD_HEARTS_KING), Ok(Next(2)));
+        assert_eq!(game.moves_by_priority(), vec![CARD_HEARTS_SEVEN, CARD_HEARTS_NINE, CARD_HEARTS_QUEEN]);
+    }
+
+    #[test]
+    fn prediction_is_met_when_enough_points_are_captured() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_CLUBS_TEN])),
+            Player::new(1, Hand::new([CARD_CLUBS_SEVEN])),
+            Player::new(2, Hand::new([CARD_CLUBS_EIGHT])),
+            Player::new(3, Hand::new([CARD_CLUBS_NINE])),
+        ];
+        for card in CARDS[7..46].iter() {
+            players.get_mut(0).pile_mut().add_card(*card);
+        }
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        game.set_declarer(0);
+        game.set_prediction(Prediction(50));
+        assert_eq!(game.play_card(1, CARD_CLUBS_SEVEN), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_CLUBS_EIGHT), Ok(Next(3)));
+        assert_eq!(game.play_card(3, CARD_CLUBS_NINE), Ok(Next(0)));
+        assert_eq!(game.prediction_met(), None);
+        assert_eq!(game.play_card(0, CARD_CLUBS_TEN), Ok(Last));
+        assert_eq!(game.kibitz().pile(0).score(), 52);
+        assert_eq!(game.prediction_met(), Some(true));
+    }
+
+    #[test]
+    fn valat_is_not_possible_after_an_opponent_wins_a_trick() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT, CARD_SPADES_EIGHT])),
+            Player::new(1, Hand::new([CARD_HEARTS_SEVEN, CARD_SPADES_KING])),
+            Player::new(2, Hand::new([CARD_HEARTS_KING, CARD_SPADES_SEVEN])),
+            Player::new(3, Hand::new([CARD_HEARTS_NINE, CARD_SPADES_NINE])),
+        ];
+        let mut game = StandardGame::with_contract(players.as_mut_slice(), VALAT_NORMAL, Hearts, vec![]);
+        game.set_declarer(1);
+        assert!(game.valat_still_possible());
+        for &(player, card) in [(1, CARD_HEARTS_SEVEN), (2, CARD_HEARTS_KING),
+                                (3, CARD_HEARTS_NINE), (0, CARD_HEARTS_EIGHT)].iter() {
+            assert!(game.play_card(player, card).is_ok());
+        }
+        assert!(!game.valat_still_possible());
+        for &(player, card) in [(2, CARD_SPADES_SEVEN), (3, CARD_SPADES_NINE),
+                                (0, CARD_SPADES_EIGHT), (1, CARD_SPADES_KING)].iter() {
+            assert!(game.play_card(player, card).is_ok());
+        }
+        assert!(!game.valat_still_possible());
+    }
+
+    #[test]
+    fn kings_are_not_achievable_after_the_opponents_capture_a_king() {
+        use bonuses::{Kings, Trula};
+
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT, CARD_SPADES_EIGHT])),
+            Player::new(1, Hand::new([CARD_HEARTS_SEVEN, CARD_SPADES_KING])),
+            Player::new(2, Hand::new([CARD_HEARTS_KING, CARD_SPADES_SEVEN])),
+            Player::new(3, Hand::new([CARD_HEARTS_NINE, CARD_SPADES_NINE])),
+        ];
+        players.get_mut(1).set_partner(3);
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Clubs, vec![]);
+        game.set_declarer(1);
+        assert!(game.achievable_bonuses(1).contains(&Kings));
+        for &(player, card) in [(1, CARD_HEARTS_SEVEN), (2, CARD_HEARTS_KING),
+                                (3, CARD_HEARTS_NINE), (0, CARD_HEARTS_EIGHT)].iter() {
+            assert!(game.play_card(player, card).is_ok());
+        }
+        let achievable = game.achievable_bonuses(1);
+        assert!(!achievable.contains(&Kings));
+        assert!(achievable.contains(&Trula));
+        assert!(game.achievable_bonuses(2).contains(&Kings));
+    }
+
+    #[test]
+    fn winner_of_the_trick_leads_the_current_trick() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_TAROCK_SKIS, CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_TAROCK_10, CARD_HEARTS_NINE])),
+            Player::new(2, Hand::new([CARD_HEARTS_JACK, CARD_CLUBS_EIGHT])),
+            Player::new(3, Hand::new([CARD_TAROCK_MOND, CARD_SPADES_JACK])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert_eq!(game.current_leader(), 1);
+        assert_eq!(game.play_card(1, CARD_HEARTS_NINE), Ok(Next(2)));
+        assert_eq!(game.current_leader(), 1);
+        assert_eq!(game.play_card(2, CARD_HEARTS_JACK), Ok(Next(3)));
+        assert_eq!(game.play_card(3, CARD_TAROCK_MOND), Ok(Next(0)));
+        assert_eq!(game.play_card(0, CARD_HEARTS_EIGHT), Ok(Next(3)));
+        assert_eq!(game.current_leader(), 3);
+        assert_eq!(game.play_card(3, CARD_SPADES_JACK), Ok(Next(0)));
+        assert_eq!(game.current_leader(), 3);
+    }
+
+    #[test]
+    fn suits_led_include_tarock_leads_and_the_current_trick() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_SPADES_KING, CARD_TAROCK_5, CARD_HEARTS_KING])),
+            Player::new(1, Hand::new([CARD_SPADES_SEVEN, CARD_TAROCK_10, CARD_HEARTS_SEVEN])),
+            Player::new(2, Hand::new([CARD_SPADES_EIGHT, CARD_TAROCK_3, CARD_HEARTS_EIGHT])),
+            Player::new(3, Hand::new([CARD_SPADES_NINE, CARD_TAROCK_4, CARD_HEARTS_NINE])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Clubs, vec![]);
+        assert!(game.suits_led().is_empty());
+        for &(player, card) in [(1, CARD_SPADES_SEVEN), (2, CARD_SPADES_EIGHT), (3, CARD_SPADES_NINE),
+                                (0, CARD_SPADES_KING), (0, CARD_TAROCK_5), (1, CARD_TAROCK_10),
+                                (2, CARD_TAROCK_3), (3, CARD_TAROCK_4), (1, CARD_HEARTS_SEVEN)].iter() {
+            assert!(game.play_card(player, card).is_ok());
+        }
+        let suits = game.suits_led();
+        assert_eq!(suits.len(), 3);
+        assert!(suits.contains(&Some(Spades)));
+        assert!(suits.contains(&None));
+        assert!(suits.contains(&Some(Hearts)));
+    }
+
+    #[test]
+    fn pagat_picked_up_in_the_exchange_is_in_the_declarer_hand() {
+        use bonuses::{PagatUltimo, announcement_consistent};
+
+        let mut players = players();
+        for &card in [CARD_HEARTS_SEVEN, CARD_HEARTS_EIGHT, CARD_TAROCK_5].iter() {
+            players.get_mut(1).hand_mut().add_card(card);
+        }
+        assert!(!announcement_consistent(&set![PagatUltimo], players[1].hand(), Some(Clubs)));
+        // The declarer takes the pagat from the talon and discards a card.
+        players.get_mut(1).hand_mut().add_card(CARD_TAROCK_PAGAT);
+        players.get_mut(1).hand_mut().remove_card(&CARD_HEARTS_SEVEN);
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Clubs, vec![CARD_HEARTS_SEVEN]);
+        game.set_declarer(1);
+        assert!(game.declarer_hand().has_card(&CARD_TAROCK_PAGAT));
+        assert!(announcement_consistent(&set![PagatUltimo], game.declarer_hand(), Some(Clubs)));
+    }
+
+    #[test]
+    fn mond_is_boss_after_the_skis_is_played() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT, CARD_TAROCK_2])),
+            Player::new(1, Hand::new([CARD_TAROCK_SKIS, CARD_TAROCK_5])),
+            Player::new(2, Hand::new([CARD_TAROCK_3, CARD_TAROCK_MOND])),
+            Player::new(3, Hand::new([CARD_TAROCK_4, CARD_HEARTS_NINE])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        assert!(!game.mond_is_boss());
+        assert_eq!(game.play_card(1, CARD_TAROCK_SKIS), Ok(Next(2)));
+        assert!(game.mond_is_boss());
+        for &(player, card) in [(2, CARD_TAROCK_3), (3, CARD_TAROCK_4), (0, CARD_TAROCK_2)].iter() {
+            assert!(game.play_card(player, card).is_ok());
+        }
+        assert!(game.mond_is_boss());
+        assert_eq!(game.play_card(1, CARD_TAROCK_5), Ok(Next(2)));
+        assert_eq!(game.play_card(2, CARD_TAROCK_MOND), Ok(Next(3)));
+        assert!(!game.mond_is_boss());
+    }
+
+    #[test]
+    fn trick_takers_in_klop_capture_the_cards_of_the_tricks() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT, CARD_SPADES_KING, CARD_CLUBS_EIGHT])),
+            Player::new(1, Hand::new([CARD_HEARTS_KING, CARD_SPADES_EIGHT, CARD_CLUBS_NINE])),
+            Player::new(2, Hand::new([CARD_HEARTS_SEVEN, CARD_SPADES_SEVEN, CARD_TAROCK_2])),
+            Player::new(3, Hand::new([CARD_HEARTS_NINE, CARD_SPADES_NINE, CARD_CLUBS_SEVEN])),
+        ];
+        let mut game = StandardGame::with_contract(players.as_mut_slice(), KLOP, Hearts, vec![]);
+        for &(player, card) in [(1, CARD_HEARTS_KING), (2, CARD_HEARTS_SEVEN), (3, CARD_HEARTS_NINE),
+                                (0, CARD_HEARTS_EIGHT), (1, CARD_SPADES_EIGHT), (2, CARD_SPADES_SEVEN),
+                                (3, CARD_SPADES_NINE), (0, CARD_SPADES_KING), (0, CARD_CLUBS_EIGHT),
+                                (1, CARD_CLUBS_NINE), (2, CARD_TAROCK_2), (3, CARD_CLUBS_SEVEN)].iter() {
+            assert!(game.play_card(player, card).is_ok());
+        }
+        assert_eq!(game.trick_taker_history(), vec![1, 0, 2]);
+        let view = game.kibitz();
+        assert!(view.pile(1).cards().contains(&CARD_HEARTS_KING));
+        assert!(view.pile(0).cards().contains(&CARD_SPADES_NINE));
+        assert!(view.pile(2).cards().contains(&CARD_CLUBS_NINE));
+        assert_eq!(view.pile(3).size(), 0);
+    }
+
+    #[test]
+    #[should_fail]
+    fn points_behind_fails_without_a_declarer() {
+        let mut players = players();
+        let game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        game.points_behind();
+    }
 }
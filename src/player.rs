@@ -1,5 +1,7 @@
+use std::collections::HashSet;
+
 use bonuses::BonusType;
-use cards::{Hand, Pile, CardDeal, Talon};
+use cards::{Card, CardSuit, SuitCard, King, Hand, Pile, CardDeal, Talon, Trick, NUM_CARDS};
 use contracts::Contract;
 
 pub type PlayerId = u64;
@@ -116,6 +118,46 @@ impl Players {
         }
     }
 
+    // Returns true if the called king is in the talon and not in any of the hands.
+    pub fn king_in_talon(&self, king: CardSuit, talon: &Talon) -> bool {
+        let card = SuitCard(King, king);
+        talon.cards().contains(&card) && !self.players.iter().any(|player| player.hand().has_card(&card))
+    }
+
+    // Sets the holder of the called king as the partner of the declarer and
+    // returns him. The declarer plays alone and no partner is set when he
+    // holds the king himself or the king is in the talon.
+    pub fn resolve_partner(&mut self, declarer: PlayerId, king: CardSuit, talon: &Talon) -> Option<PlayerId> {
+        if self.king_in_talon(king, talon) {
+            return None
+        }
+        let card = SuitCard(King, king);
+        let partner = self.players.iter()
+            .find(|player| player.id() != declarer && player.hand().has_card(&card))
+            .map(|player| player.id());
+        match partner {
+            Some(partner) => self.player_mut(declarer).set_partner(partner),
+            None => {}
+        }
+        partner
+    }
+
+    // Returns the cards that appear more than once in the hands and piles of
+    // the players and the talon. A healthy game has no duplicates.
+    pub fn find_duplicates(&self, talon: &Talon) -> Vec<Card> {
+        let mut seen = HashSet::new();
+        let mut duplicates = Vec::new();
+        let cards = self.players.iter()
+            .flat_map(|player| player.hand().cards().chain(player.pile().cards().iter()))
+            .chain(talon.cards().iter());
+        for card in cards {
+            if !seen.insert(*card) && !duplicates.contains(card) {
+                duplicates.push(*card);
+            }
+        }
+        duplicates
+    }
+
     // Returns a reference to a player with a given id.
     pub fn player(&self, id: PlayerId) -> &Player {
         &self.players[id as uint]
@@ -160,6 +202,31 @@ impl<'a> ContractPlayers<'a> {
         scoring
     }
 
+    // Returns the points captured by the partner of the declarer or `None` if
+    // the declarer plays alone.
+    pub fn partner_pile_score(&self) -> Option<uint> {
+        self.declarer().partner().map(|partner| self.player(partner).pile().score() as uint)
+    }
+
+    // Returns the players playing against the declarer and his partner.
+    pub fn opponents(&self) -> Vec<&Player> {
+        let declarer = self.declarer();
+        self.players.players.iter()
+            .filter(|player| player.id() != declarer.id() && Some(player.id()) != declarer.partner())
+            .collect()
+    }
+
+    // Returns the tricks won by the declarer or his partner. Each trick in the
+    // log is paired with the player that won it.
+    pub fn declarer_side_tricks(&self, trick_log: &[(PlayerId, Trick)]) -> Vec<Trick> {
+        let declarer = self.declarer().id();
+        let partner = self.declarer().partner();
+        trick_log.iter()
+            .filter(|&&(winner, _)| winner == declarer || Some(winner) == partner)
+            .map(|&(_, ref trick)| trick.clone())
+            .collect()
+    }
+
     // Returns the currently played contract.
     pub fn contract(&self) -> Contract {
         self.contract
@@ -176,6 +243,52 @@ impl<'a> ContractPlayers<'a> {
     }
 }
 
+// A problem found when validating dealt cards.
+#[deriving(Show, Eq, PartialEq)]
+pub enum DealIssue {
+    // The total number of dealt cards is not the size of the deck.
+    WrongNumberOfCards(uint),
+    // The card was dealt more than once.
+    DuplicateCard(Card),
+}
+
+// An error listing all the problems with dealt cards.
+#[deriving(Show, Eq, PartialEq)]
+pub struct ValidationError {
+    issues: Vec<DealIssue>,
+}
+
+impl ValidationError {
+    // Returns all the problems found.
+    pub fn issues(&self) -> &[DealIssue] {
+        self.issues.as_slice()
+    }
+}
+
+// Checks that the hands and the talon together form a complete deck where
+// every card was dealt exactly once.
+pub fn validate_hands(hands: &[Hand], talon: &[Card]) -> Result<(), ValidationError> {
+    let mut issues = Vec::new();
+    let mut seen = HashSet::new();
+    let mut duplicates = HashSet::new();
+    let mut num_cards = 0u;
+    for card in hands.iter().flat_map(|hand| hand.cards()).chain(talon.iter()) {
+        num_cards += 1;
+        if !seen.insert(*card) && duplicates.insert(*card) {
+            issues.push(DuplicateCard(*card));
+        }
+    }
+    if num_cards != NUM_CARDS {
+        issues.push(WrongNumberOfCards(num_cards));
+    }
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError { issues: issues })
+    }
+}
+
+#[deriving(Clone)]
 pub struct PlayerTurn {
     current_index: uint,
     num_players: uint,
@@ -226,10 +339,40 @@ impl PlayerTurn {
     pub fn current(&self) -> &PlayerId {
         &self.players[self.current_index]
     }
+
+    // Captures the turn state including the players that were removed.
+    pub fn snapshot(&self) -> TurnSnapshot {
+        TurnSnapshot {
+            current_index: self.current_index,
+            num_players: self.num_players,
+            started_with: self.started_with,
+            players: self.players.clone(),
+        }
+    }
+
+    // Constructs the turn state captured by the snapshot.
+    pub fn restore(snapshot: &TurnSnapshot) -> PlayerTurn {
+        PlayerTurn {
+            current_index: snapshot.current_index,
+            num_players: snapshot.num_players,
+            started_with: snapshot.started_with,
+            players: snapshot.players.clone(),
+        }
+    }
+}
+
+// A saved state of `PlayerTurn` that can be restored later.
+#[deriving(Clone, Show, Eq, PartialEq, Encodable, Decodable)]
+pub struct TurnSnapshot {
+    current_index: uint,
+    num_players: uint,
+    started_with: PlayerId,
+    players: Vec<PlayerId>,
 }
 
 #[cfg(test)]
 mod test {
+    use cards::*;
     use contracts::{SoloWithout, Standard, Two};
     use super::*;
 
@@ -306,4 +449,105 @@ mod test {
         assert_eq!(1, *order.remove());
         assert_eq!(1, order.current_players())
     }
+
+    #[test]
+    fn complete_deal_is_valid() {
+        let hands = [Hand::new(CARDS[0 .. 24]), Hand::new(CARDS[24 .. 48])];
+        assert_eq!(validate_hands(&hands, CARDS[48 .. 54]), Ok(()));
+    }
+
+    #[test]
+    fn cards_dealt_twice_are_reported() {
+        let hands = [Hand::new(CARDS[0 .. 24]), Hand::new(CARDS[22 .. 48])];
+        let error = validate_hands(&hands, CARDS[48 .. 54]).unwrap_err();
+        assert_eq!(error.issues().len(), 3);
+        assert!(error.issues().contains(&DuplicateCard(CARDS[22])));
+        assert!(error.issues().contains(&DuplicateCard(CARDS[23])));
+        assert!(error.issues().contains(&WrongNumberOfCards(56)));
+    }
+
+    #[test]
+    fn missing_cards_are_reported() {
+        let hands = [Hand::new(CARDS[0 .. 24]), Hand::new(CARDS[24 .. 47])];
+        let error = validate_hands(&hands, CARDS[48 .. 54]).unwrap_err();
+        assert_eq!(error.issues(), [WrongNumberOfCards(53)].as_slice());
+    }
+
+    #[test]
+    fn tricks_won_by_declarer_and_partner_are_returned() {
+        let mut players = Players::new(4);
+        players.player_mut(1).set_partner(3);
+        let cp = players.play_contract(1, Standard(Two));
+        let log = [
+            (0, Trick::from_cards([CARD_HEARTS_KING, CARD_HEARTS_SEVEN, CARD_HEARTS_EIGHT, CARD_HEARTS_NINE])),
+            (1, Trick::from_cards([CARD_TAROCK_2, CARD_TAROCK_3, CARD_TAROCK_4, CARD_TAROCK_5])),
+            (2, Trick::from_cards([CARD_CLUBS_KING, CARD_CLUBS_SEVEN, CARD_CLUBS_EIGHT, CARD_CLUBS_NINE])),
+            (3, Trick::from_cards([CARD_SPADES_KING, CARD_SPADES_SEVEN, CARD_SPADES_EIGHT, CARD_SPADES_NINE])),
+        ];
+        let tricks = cp.declarer_side_tricks(&log);
+        assert_eq!(tricks.len(), 2);
+        assert_eq!(tricks[0].first(), Some(CARD_TAROCK_2));
+        assert_eq!(tricks[1].first(), Some(CARD_SPADES_KING));
+    }
+
+    #[test]
+    fn no_partner_is_resolved_when_the_called_king_is_in_the_talon() {
+        // The talon is dealt first, the king of clubs is the second card of player 0.
+        let mut players = Players::new(4);
+        let talon = players.deal(deal_four_player_standard(&CARDS));
+        assert!(!players.king_in_talon(Clubs, &talon));
+        assert_eq!(players.resolve_partner(1, Clubs, &talon), Some(0));
+        assert_eq!(players.player(1).partner(), Some(0));
+
+        let mut players = Players::new(4);
+        let mut cards = CARDS.to_vec();
+        cards.as_mut_slice().swap(0, 7);
+        let talon = players.deal(deal_four_player_standard(cards.as_slice()));
+        assert!(players.king_in_talon(Clubs, &talon));
+        assert_eq!(players.resolve_partner(1, Clubs, &talon), None);
+        assert_eq!(players.player(1).partner(), None);
+    }
+
+    #[test]
+    fn duplicate_cards_in_the_game_are_found() {
+        let mut players = Players::new(4);
+        let talon = players.deal(deal_four_player_standard(&CARDS));
+        assert_eq!(players.find_duplicates(&talon), vec![]);
+        players.player_mut(2).pile_mut().add_card(CARD_TAROCK_MOND);
+        assert_eq!(players.find_duplicates(&talon), vec![CARD_TAROCK_MOND]);
+    }
+
+    #[test]
+    fn restored_turn_keeps_the_removed_players() {
+        let mut turn = PlayerTurn::start_with(4, 1);
+        turn.next();
+        turn.remove();
+        let snapshot = turn.snapshot();
+        assert_eq!(*turn.current(), 3);
+        turn.next();
+        turn.remove();
+        assert_eq!(turn.current_players(), 2);
+        let mut restored = PlayerTurn::restore(&snapshot);
+        assert_eq!(restored.snapshot(), snapshot);
+        assert_eq!(restored.current_players(), 3);
+        assert_eq!(*restored.current(), 3);
+        assert_eq!(*restored.next(), 0);
+        assert_eq!(*restored.next(), 1);
+        assert_eq!(*restored.next(), 3);
+    }
+
+    #[test]
+    fn partner_pile_is_scored_separately() {
+        let mut players = Players::new(4);
+        for &card in [CARD_HEARTS_KING, CARD_HEARTS_SEVEN, CARD_HEARTS_EIGHT, CARD_TAROCK_MOND].iter() {
+            players.player_mut(2).pile_mut().add_card(card);
+        }
+        {
+            let cp = players.play_contract(0, Standard(Two));
+            assert_eq!(cp.partner_pile_score(), None);
+        }
+        players.player_mut(0).set_partner(2);
+        let cp = players.play_contract(0, Standard(Two));
+        assert_eq!(cp.partner_pile_score(), Some(9));
+    }
 }
@@ -0,0 +1,280 @@
+use std::collections::{HashMap, HashSet};
+
+use bidding::BidAction;
+use bonuses::BonusType;
+use cards::{Card, CardSuit, CardSet, Hand, Trick, NUM_TAROCKS};
+use contracts::{Contract, move_validator, valid_moves};
+use game::{StandardGame, ContractGame};
+use player::PlayerId;
+use scoring::PlayerScores;
+
+// A card played by a player.
+pub type Move = (PlayerId, Card);
+
+// What is known about the cards a player still holds, inferred from the
+// cards he played.
+#[deriving(Clone, Show, Eq, PartialEq)]
+pub struct SuitConstraints {
+    // Suits the player is known to be void in.
+    pub voids: HashSet<CardSuit>,
+    // True if the player is known to have no tarocks left.
+    pub no_tarocks: bool,
+    // Suits the player was last seen playing and is not known to be void in.
+    pub holdings: HashSet<CardSuit>,
+}
+
+impl SuitConstraints {
+    // Constructs constraints of a player that has not played a card yet.
+    pub fn new() -> SuitConstraints {
+        SuitConstraints {
+            voids: HashSet::new(),
+            no_tarocks: false,
+            holdings: HashSet::new(),
+        }
+    }
+
+    // Returns true if the player is known to have no cards of the suit.
+    pub fn is_void(&self, suit: &CardSuit) -> bool {
+        self.voids.contains(suit)
+    }
+
+    // Returns true if the player may still hold the card.
+    pub fn may_hold(&self, card: &Card) -> bool {
+        match card.suit() {
+            Some(suit) => !self.is_void(&suit),
+            None => !self.no_tarocks,
+        }
+    }
+
+    // Updates the constraints with a card played to a trick led with `led`.
+    fn observe(&mut self, led: &Card, played: &Card) {
+        match (led.suit(), played.suit()) {
+            (Some(led_suit), Some(suit)) if led_suit != suit => {
+                // Neither the led suit nor a tarock was played.
+                self.set_void(led_suit);
+                self.no_tarocks = true;
+            }
+            (Some(led_suit), None) => self.set_void(led_suit),
+            (None, Some(_)) => self.no_tarocks = true,
+            _ => {}
+        }
+        match played.suit() {
+            Some(suit) if !self.is_void(&suit) => { self.holdings.insert(suit); }
+            _ => {}
+        }
+    }
+
+    fn set_void(&mut self, suit: CardSuit) {
+        self.voids.insert(suit);
+        self.holdings.remove(&suit);
+    }
+}
+
+// Infers the suit constraints of every player from the moves played so far
+// using the standard rules for following suit.
+// Moves are grouped into tricks of `num_players` cards, the first card of each
+// trick being the lead.
+pub fn player_constraints(moves: &[Move], num_players: uint) -> HashMap<PlayerId, SuitConstraints> {
+    let mut constraints = HashMap::new();
+    for trick in moves.chunks(num_players) {
+        let (_, led) = trick[0];
+        for &(player, card) in trick.iter() {
+            if !constraints.contains_key(&player) {
+                constraints.insert(player, SuitConstraints::new());
+            }
+            constraints.find_mut(&player).unwrap().observe(&led, &card);
+        }
+    }
+    constraints
+}
+
+// Returns the number of tarocks that were not played yet and are not in the
+// hand of the perspective player, i.e. the tarocks held by the other players
+// or lying in the talon.
+// The number of players is taken like in `player_constraints`, the count does
+// not depend on how the moves are grouped into tricks.
+pub fn tarocks_remaining(moves: &[Move], _num_players: uint, perspective_hand: &Hand) -> uint {
+    let played = moves.iter().filter(|&&(_, card)| card.is_tarock()).count();
+    let held = perspective_hand.cards().filter(|card| card.is_tarock()).count();
+    NUM_TAROCKS - played - held
+}
+
+// Returns the number of legal moves the player had at each move of the log,
+// replaying the moves from the initial hands. Moves are grouped into tricks of
+// one card per hand, the first card of each trick being the lead.
+pub fn branching_factors(log: &[Move], initial_hands: &[Hand], contract: &Contract) -> Vec<uint> {
+    let validator = move_validator(contract);
+    let mut hands = initial_hands.to_vec();
+    let mut factors = Vec::with_capacity(log.len());
+    for moves in log.chunks(initial_hands.len()) {
+        let mut trick = Trick::empty();
+        for &(player, card) in moves.iter() {
+            let hand = hands.get_mut(player as uint);
+            factors.push(valid_moves(validator, hand, &trick).len());
+            hand.remove_card(&card);
+            trick.add_card(card);
+        }
+    }
+    factors
+}
+
+// Estimates the probability that each opponent holds the card, assuming the
+// unseen cards are dealt uniformly between the opponents' hands (and the
+// talon, if the hands do not account for all of them). Void constraints are
+// ignored.
+pub fn holding_probability(card: &Card,
+                           unseen: &CardSet,
+                           opponents: &[(PlayerId, uint)]) -> HashMap<PlayerId, f64> {
+    opponents.iter()
+        .map(|&(player, hand_size)| {
+            let probability = if unseen.contains(card) {
+                hand_size as f64 / unseen.len() as f64
+            } else {
+                0.0
+            };
+            (player, probability)
+        })
+        .collect()
+}
+
+// A record of a complete round that can be stored in a match log.
+#[deriving(Show, PartialEq, Encodable, Decodable)]
+pub struct RoundRecord {
+    pub dealer: PlayerId,
+    pub declarer: PlayerId,
+    pub contract: Contract,
+    // The suit of the called king, if a king was called.
+    pub king: Option<CardSuit>,
+    pub bids: Vec<BidAction>,
+    // Bonuses announced by the players.
+    pub announcements: Vec<(PlayerId, BonusType)>,
+    // Cards played in the order they were played.
+    pub moves: Vec<Move>,
+    pub scores: PlayerScores,
+}
+
+impl RoundRecord {
+    // Constructs a record of a round from the bidding, the announcements and
+    // the played game together with the resulting scores.
+    pub fn new(dealer: PlayerId,
+               declarer: PlayerId,
+               king: Option<CardSuit>,
+               bids: &[BidAction],
+               announcements: &[(PlayerId, BonusType)],
+               game: &StandardGame,
+               scores: PlayerScores) -> RoundRecord {
+        RoundRecord {
+            dealer: dealer,
+            declarer: declarer,
+            contract: game.contract(),
+            king: king,
+            bids: bids.to_vec(),
+            announcements: announcements.to_vec(),
+            moves: game.moves(),
+            scores: scores,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serialize::json;
+
+    use std::collections::HashMap;
+
+    use bidding::{PlayerBid, PlayerPass};
+    use bonuses::Trula;
+    use cards::*;
+    use contracts::{Three, STANDARD_THREE};
+    use game::{StandardGame, ContractGame};
+    use player::Player;
+
+    use super::{player_constraints, holding_probability, tarocks_remaining, branching_factors, RoundRecord};
+
+    #[test]
+    fn player_trumping_a_suit_is_void_in_it() {
+        let moves = [(1, CARD_HEARTS_NINE), (2, CARD_HEARTS_JACK),
+                     (3, CARD_TAROCK_MOND), (0, CARD_HEARTS_EIGHT)];
+        let constraints = player_constraints(&moves, 4);
+        assert!(constraints[3].is_void(&Hearts));
+        assert!(!constraints[3].no_tarocks);
+        assert!(!constraints[2].is_void(&Hearts));
+        assert!(constraints[2].holdings.contains(&Hearts));
+    }
+
+    #[test]
+    fn player_discarding_a_suit_has_no_tarocks() {
+        let moves = [(1, CARD_SPADES_NINE), (2, CARD_CLUBS_JACK),
+                     (3, CARD_SPADES_KING), (0, CARD_SPADES_EIGHT),
+                     (3, CARD_CLUBS_SEVEN), (0, CARD_TAROCK_2),
+                     (1, CARD_CLUBS_KING), (2, CARD_CLUBS_EIGHT)];
+        let constraints = player_constraints(&moves, 4);
+        assert!(constraints[2].is_void(&Spades));
+        assert!(constraints[2].no_tarocks);
+        assert!(constraints[2].holdings.contains(&Clubs));
+        assert!(constraints[0].is_void(&Clubs));
+        assert!(!constraints[0].no_tarocks);
+    }
+
+    #[test]
+    fn round_record_survives_a_json_round_trip() {
+        let mut players = vec![
+            Player::new(0, Hand::new([CARD_HEARTS_EIGHT])),
+            Player::new(1, Hand::new([CARD_HEARTS_NINE])),
+            Player::new(2, Hand::new([CARD_HEARTS_KING])),
+            Player::new(3, Hand::new([CARD_TAROCK_MOND])),
+        ];
+        let mut game = StandardGame::new(players.as_mut_slice(), Three, Hearts, vec![]);
+        for &(player, card) in [(1, CARD_HEARTS_NINE), (2, CARD_HEARTS_KING),
+                                (3, CARD_TAROCK_MOND), (0, CARD_HEARTS_EIGHT)].iter() {
+            assert!(game.play_card(player, card).is_ok());
+        }
+        let mut scores = HashMap::new();
+        scores.insert(1, -10);
+        scores.insert(2, -10);
+        let record = RoundRecord::new(0, 1, Some(Hearts),
+                                      [PlayerBid(1, STANDARD_THREE), PlayerPass(2),
+                                       PlayerPass(3), PlayerPass(0)],
+                                      [(1, Trula)], &game, scores);
+        assert_eq!(record.contract, STANDARD_THREE);
+        assert_eq!(record.moves.len(), 4);
+
+        let encoded = json::encode(&record);
+        let decoded: RoundRecord = json::decode(encoded.as_slice()).unwrap();
+        assert_eq!(decoded, record);
+    }
+
+    #[test]
+    fn card_is_held_equally_likely_by_opponents_with_equal_hands() {
+        let unseen = set![CARD_HEARTS_KING, CARD_HEARTS_SEVEN, CARD_TAROCK_2, CARD_TAROCK_MOND];
+        let probability = holding_probability(&CARD_TAROCK_MOND, &unseen, [(1, 2), (3, 2)]);
+        assert_eq!(probability[1], 0.5);
+        assert_eq!(probability[3], 0.5);
+        let probability = holding_probability(&CARD_TAROCK_SKIS, &unseen, [(1, 2), (3, 2)]);
+        assert_eq!(probability[1], 0.0);
+    }
+
+    #[test]
+    fn remaining_tarocks_exclude_played_and_held_ones() {
+        let moves = [(1, CARD_TAROCK_10), (2, CARD_TAROCK_MOND), (3, CARD_TAROCK_2), (0, CARD_TAROCK_3),
+                     (2, CARD_HEARTS_KING), (3, CARD_TAROCK_SKIS)];
+        let hand = Hand::new([CARD_TAROCK_PAGAT, CARD_TAROCK_20, CARD_CLUBS_SEVEN]);
+        assert_eq!(tarocks_remaining(&moves, 4, &hand), 15);
+    }
+
+    #[test]
+    fn every_recorded_move_had_at_least_one_legal_alternative() {
+        let hands = [
+            Hand::new([CARD_HEARTS_EIGHT, CARD_SPADES_KING, CARD_TAROCK_2]),
+            Hand::new([CARD_HEARTS_NINE, CARD_HEARTS_KING, CARD_CLUBS_SEVEN]),
+            Hand::new([CARD_HEARTS_SEVEN, CARD_SPADES_SEVEN, CARD_TAROCK_MOND]),
+            Hand::new([CARD_TAROCK_SKIS, CARD_SPADES_NINE, CARD_CLUBS_KING]),
+        ];
+        let log = [(1, CARD_HEARTS_NINE), (2, CARD_HEARTS_SEVEN), (3, CARD_TAROCK_SKIS), (0, CARD_HEARTS_EIGHT),
+                   (3, CARD_SPADES_NINE), (0, CARD_SPADES_KING), (1, CARD_HEARTS_KING), (2, CARD_SPADES_SEVEN)];
+        let factors = branching_factors(&log, &hands, &STANDARD_THREE);
+        assert_eq!(factors.len(), log.len());
+        assert!(factors.iter().all(|&factor| factor >= 1));
+        assert_eq!(factors, vec![3, 1, 1, 1, 2, 1, 2, 1]);
+    }
+}
@@ -1,16 +1,97 @@
 use std::collections::HashMap;
 
-use cards::{Pile, HALF_POINTS, NUM_CARDS, TALON_SIZE};
-use contracts::{Klop};
+use bonuses::Bonus;
+use cards::{Card, Pile, MAX_POINTS, HALF_POINTS, NUM_CARDS, TALON_SIZE};
+use contracts::{Contract, Klop};
 use player::{PlayerId, ContractPlayers};
 
 // A map of scores for individual players.
 // Only players that have the score != 0 are included.
 pub type PlayerScores = HashMap<PlayerId, int>;
 
+// The outcome of a finished round from the declarer's point of view.
+pub struct ScoreReport {
+    // True if the declarer won the contract.
+    pub declarer_won: bool,
+    // Points captured by the declarer and his partner.
+    pub declarer_points: int,
+    // Points captured by the opponents.
+    pub opponent_points: int,
+    // The score of the declarer including bonuses.
+    pub score: int,
+    // Bonuses achieved by the declarer.
+    pub bonuses: Vec<Bonus>,
+}
+
+// Returns a one-line summary of a finished round for logging, e.g.
+// "Solo Two: declarer won, captured 42-28, +50 (Trula +10)".
+pub fn summarize(report: &ScoreReport, contract: &Contract) -> String {
+    let outcome = if report.declarer_won { "won" } else { "lost" };
+    let mut summary = format!("{}: declarer {}, captured {}-{}, {}",
+                              contract.name(),
+                              outcome,
+                              report.declarer_points,
+                              report.opponent_points,
+                              signed(report.score));
+    if !report.bonuses.is_empty() {
+        let bonuses = report.bonuses.iter()
+            .map(|bonus| format!("{} {}", bonus.bonus_type().name(), signed(bonus.value())))
+            .collect::<Vec<String>>();
+        summary.push_str(format!(" ({})", bonuses.connect(", ")).as_slice());
+    }
+    summary
+}
+
+// Formats the score with an explicit sign.
+fn signed(score: int) -> String {
+    if score < 0 {
+        format!("{}", score)
+    } else {
+        format!("+{}", score)
+    }
+}
+
+// Optional rules used when scoring normal contracts.
+pub struct NormalRules {
+    // The losing side capturing less points than the threshold is schneider.
+    pub schneider_threshold: Option<uint>,
+    // Points added to the score of the contract when the losing side is schneider.
+    pub schneider_penalty: int,
+}
+
+impl NormalRules {
+    // Constructs the rules without any of the optional rules enabled.
+    pub fn new() -> NormalRules {
+        NormalRules {
+            schneider_threshold: None,
+            schneider_penalty: 0,
+        }
+    }
+}
+
+// Returns true if the captured points are below the schneider threshold.
+pub fn is_schneider(points: uint, threshold: uint) -> bool {
+    points < threshold
+}
+
 // Calculate the scores for the players depending on the contract played.
 // At least one player will always score.
 pub fn score(players: &ContractPlayers) -> PlayerScores {
+    score_with_rules(players, &NormalRules::new())
+}
+
+// Calculate the scores for the players using the rules for normal contracts.
+pub fn score_with_rules(players: &ContractPlayers, rules: &NormalRules) -> PlayerScores {
+    score_contract(players, rules, None)
+}
+
+// Calculate the scores for the players when the declarer did not take the
+// whole talon. The points left in the talon belong to the opponents.
+pub fn score_with_talon(players: &ContractPlayers, rules: &NormalRules, talon: &[Card]) -> PlayerScores {
+    score_contract(players, rules, Some(talon))
+}
+
+fn score_contract(players: &ContractPlayers, rules: &NormalRules, talon: Option<&[Card]>) -> PlayerScores {
     if players.contract().is_klop() {
         score_klop(players)
     } else if players.contract().is_beggar() {
@@ -18,12 +99,134 @@ pub fn score(players: &ContractPlayers) -> PlayerScores {
     } else if players.contract().is_valat() {
         score_valat(players)
     } else {
-        score_normal(players)
+        score_normal(players, rules, talon)
+    }
+}
+
+// Reason a radlc was awarded to a player.
+#[deriving(Clone, Show, Eq, PartialEq)]
+pub enum RadlcAward {
+    SoloWithoutWon,
+    ValatWon,
+}
+
+// Running totals of player scores over multiple rounds.
+// A player with a pending radlc has the score of his next round doubled.
+pub struct ScoreBoard {
+    totals: PlayerScores,
+    radlci: HashMap<PlayerId, uint>,
+}
+
+impl ScoreBoard {
+    // Constructs an empty score board.
+    pub fn new() -> ScoreBoard {
+        ScoreBoard {
+            totals: HashMap::new(),
+            radlci: HashMap::new(),
+        }
+    }
+
+    // Adds the scores of a round to the totals.
+    pub fn add_round(&mut self, scores: &PlayerScores) {
+        self.add_round_with_flags(scores, [])
+    }
+
+    // Adds the scores of a round to the totals and awards radlci to players.
+    // Pending radlci are applied to the scores of this round, the awarded ones
+    // apply from the next round on.
+    pub fn add_round_with_flags(&mut self, scores: &PlayerScores, awards: &[(PlayerId, RadlcAward)]) {
+        for (&player, &score) in scores.iter() {
+            let pending = self.radlci(player);
+            let score = if pending > 0 {
+                self.radlci.insert(player, pending - 1);
+                2 * score
+            } else {
+                score
+            };
+            let total = self.total(player);
+            self.totals.insert(player, total + score);
+        }
+        for &(player, _) in awards.iter() {
+            let pending = self.radlci(player);
+            self.radlci.insert(player, pending + 1);
+        }
+    }
+
+    // Returns the total score of the player.
+    pub fn total(&self, player: PlayerId) -> int {
+        self.totals.find(&player).map(|total| *total).unwrap_or(0)
+    }
+
+    // Returns the number of radlci the player has not used yet.
+    pub fn radlci(&self, player: PlayerId) -> uint {
+        self.radlci.find(&player).map(|radlci| *radlci).unwrap_or(0)
+    }
+}
+
+// The side of players in a normal contract.
+#[deriving(Show, Eq, PartialEq)]
+pub enum Side {
+    Declarer,
+    Opponents,
+    Tie,
+}
+
+// Returns the side that captured more points.
+pub fn winning_side(declarer_pile: &Pile, opponent_pile: &Pile) -> Side {
+    let declarer_score = declarer_pile.score();
+    let opponent_score = opponent_pile.score();
+    if declarer_score > opponent_score {
+        Declarer
+    } else if declarer_score < opponent_score {
+        Opponents
+    } else {
+        Tie
+    }
+}
+
+// Returns the score of the contract: the value of the contract, positive if
+// won and negative if lost, multiplied by the contra multiplier plus the value
+// of bonuses.
+pub fn final_score(contract: &Contract, won: bool, multiplier: u32, bonuses: &[Bonus]) -> int {
+    let bonus_total = bonuses.iter().map(|bonus| bonus.value()).fold(0, |a, b| a + b);
+    score_sign(|| won) * contract.value() * multiplier as int + bonus_total
+}
+
+// Returns the total points at risk in a round: the value of the contract
+// multiplied by the contra multiplier plus the value of bonuses.
+pub fn effective_stake(contract: &Contract, multiplier: u32, bonuses: &[Bonus]) -> int {
+    final_score(contract, true, multiplier, bonuses)
+}
+
+// Returns the sum of all scores. Settled scores, where the opponents pay the
+// declarer's score, are zero-sum and always total 0. Scores as returned by
+// `score` only include the scoring players and generally do not.
+pub fn total(scores: &PlayerScores) -> int {
+    scores.values().fold(0, |a, &b| a + b)
+}
+
+// Settles the declarer's score in a three-player game so that the table nets to
+// zero: the two opponents together pay (or receive) the declarer's score.
+// When the score cannot be split evenly the opponent sitting later takes the
+// larger share.
+pub fn settle_three_player(scores: &PlayerScores, declarer: PlayerId) -> PlayerScores {
+    const NUM_PLAYERS: PlayerId = 3;
+    let mut settled = HashMap::new();
+    let value = scores.find(&declarer).map(|score| *score).unwrap_or(0);
+    if value != 0 {
+        let first_share = value / 2;
+        settled.insert(declarer, value);
+        settled.insert((declarer + 1) % NUM_PLAYERS, -first_share);
+        settled.insert((declarer + 2) % NUM_PLAYERS, -(value - first_share));
     }
+    settled
 }
 
 // Calculate the scores for normal contracts.
-fn score_normal(players: &ContractPlayers) -> PlayerScores {
+// The declarer has to capture more than half of the points. When the talon is
+// given its points are counted on the side of the opponents, which must not
+// reach the other half of the points either.
+fn score_normal(players: &ContractPlayers, rules: &NormalRules, talon: Option<&[Card]>) -> PlayerScores {
     let contract = players.contract();
     let mut pile = Pile::new();
     let scoring = players.scoring_players();
@@ -35,10 +238,28 @@ fn score_normal(players: &ContractPlayers) -> PlayerScores {
     }
     // Score all the cards from the scoring players together.
     let score = pile.score();
+    let won = score > HALF_POINTS && match talon {
+        Some(talon) => {
+            let mut opponents = Pile::new();
+            for player in players.opponents().into_iter() {
+                opponents.add_pile(player.pile());
+            }
+            for card in talon.iter() {
+                opponents.add_card(*card);
+            }
+            opponents.score() < MAX_POINTS - HALF_POINTS
+        }
+        None => true,
+    };
+    let losing_points = if won { MAX_POINTS - score } else { score };
+    let penalty = match rules.schneider_threshold {
+        Some(threshold) if is_schneider(losing_points as uint, threshold) => rules.schneider_penalty,
+        _ => 0,
+    };
     // Every scoring player gets the same amount of points.
     p.iter().map(|&player_id| {
-        let score = score_sign(|| score > HALF_POINTS) * (score + contract.value());
-        (player_id, round_score(score))
+        let score = score_sign(|| won) * (score + contract.value() + penalty);
+        (player_id, round_to_five(score))
     }).collect()
 }
 
@@ -55,7 +276,7 @@ fn score_klop(players: &ContractPlayers) -> PlayerScores {
         .find(|score| is_winner_loser(*score))
         .is_some();
     if !winner_loser {
-        scores.iter().map(|(&player_id, &score)| (player_id, round_score(score))).collect()
+        scores.iter().map(|(&player_id, &score)| (player_id, round_to_five(score))).collect()
     } else {
         // Set the max and -max scores for winner and loser respectively.
         scores.iter()
@@ -93,7 +314,7 @@ fn score_beggar(players: &ContractPlayers) -> PlayerScores {
     let mut scores = HashMap::new();
     let scoring = players.scoring_players();
     assert!(scoring.len() == 1);
-    let score = score_sign(|| scoring[0].pile().is_empty()) * contract.value();
+    let score = final_score(&contract, scoring[0].pile().is_empty(), 1, []);
     scores.insert(scoring[0].id(), score);
     scores
 }
@@ -104,7 +325,7 @@ fn score_valat(players: &ContractPlayers) -> PlayerScores {
     let mut scores = HashMap::new();
     let scoring = players.scoring_players();
     assert!(scoring.len() == 1);
-    let score = score_sign(|| scoring[0].pile().size() >= NUM_CARDS - TALON_SIZE) * contract.value();
+    let score = final_score(&contract, scoring[0].pile().size() >= NUM_CARDS - TALON_SIZE, 1, []);
     scores.insert(scoring[0].id(), score);
     scores
 }
@@ -119,17 +340,21 @@ fn score_sign(cond: || -> bool) -> int {
 }
 
 
-// Round the score to the nearest score divisible by 5.
-fn round_score(score: int) -> int {
+// Rounds the score to the nearest score divisible by 5. Halves are rounded
+// away from zero so negative scores round symmetrically.
+pub fn round_to_five(score: int) -> int {
     (score as f64 / 5.0).round() as int * 5
 }
 
 #[cfg(test)]
 mod test {
+    use bonuses::{Unannounced, Announced, Trula, Kings};
     use cards::*;
-    use contracts::{SoloWithout, Klop, Standard, Three, Two, Beggar, beggar, Valat, valat};
+    use contracts::{SoloWithout, Klop, Standard, Solo, Three, Two, Beggar, beggar, Valat, valat};
     use player::{Players, PlayerId};
 
+    use std::collections::HashMap;
+
     use super::*;
 
     fn init_cards(players: &mut Players) {
@@ -282,4 +507,167 @@ mod test {
         assert_eq!(scores.len(), 1);
         assert_eq!(scores[3], -125);
     }
+
+    #[test]
+    fn three_player_settlement_is_zero_sum() {
+        let mut scores = HashMap::new();
+        scores.insert(1, 30);
+        let settled = settle_three_player(&scores, 1);
+        assert_eq!(settled.len(), 3);
+        assert_eq!(settled[1], 30);
+        assert_eq!(settled[2], -15);
+        assert_eq!(settled[0], -15);
+    }
+
+    #[test]
+    fn three_player_settlement_splits_odd_scores() {
+        let mut scores = HashMap::new();
+        scores.insert(2, -35);
+        let settled = settle_three_player(&scores, 2);
+        assert_eq!(settled[0], 17);
+        assert_eq!(settled[1], 18);
+        assert_eq!(settled.values().fold(0, |a, b| a + *b), 0);
+    }
+
+    #[test]
+    fn round_summary_is_a_single_line() {
+        let report = ScoreReport {
+            declarer_won: true,
+            declarer_points: 42,
+            opponent_points: 28,
+            score: 50,
+            bonuses: vec![Unannounced(Trula)],
+        };
+        assert_eq!(summarize(&report, &Solo(Two)).as_slice(),
+                   "Solo Two: declarer won, captured 42-28, +50 (Trula +10)");
+    }
+
+    #[test]
+    fn round_summary_of_a_lost_contract() {
+        let report = ScoreReport {
+            declarer_won: false,
+            declarer_points: 30,
+            opponent_points: 40,
+            score: -25,
+            bonuses: vec![Unannounced(Trula), Announced(Kings)],
+        };
+        assert_eq!(summarize(&report, &Standard(Three)).as_slice(),
+                   "Three: declarer lost, captured 30-40, -25 (Trula +10, Kings +20)");
+    }
+
+    #[test]
+    fn stake_includes_contra_and_bonuses() {
+        assert_eq!(effective_stake(&Standard(Two), 1, []), 20);
+        assert_eq!(effective_stake(&Standard(Two), 2, [Unannounced(Trula)]), 50);
+        assert_eq!(effective_stake(&Standard(Two), 2, [Unannounced(Trula), Announced(Kings)]), 70);
+    }
+
+    fn pile(cards: &[Card]) -> Pile {
+        let mut pile = Pile::new();
+        for card in cards.iter() {
+            pile.add_card(*card);
+        }
+        pile
+    }
+
+    #[test]
+    fn side_with_more_points_wins() {
+        let declarer = pile(CARDS[22 .. 54]);
+        let opponents = pile(CARDS[0 .. 22]);
+        assert_eq!(declarer.score(), 40);
+        assert_eq!(opponents.score(), 30);
+        assert_eq!(winning_side(&declarer, &opponents), Declarer);
+        assert_eq!(winning_side(&opponents, &declarer), Opponents);
+        assert_eq!(winning_side(&declarer, &declarer), Tie);
+    }
+
+    #[test]
+    fn radlc_doubles_the_next_round_score() {
+        let mut board = ScoreBoard::new();
+        let mut round_one = HashMap::new();
+        round_one.insert(1, 80);
+        board.add_round_with_flags(&round_one, [(1, SoloWithoutWon)]);
+        assert_eq!(board.total(1), 80);
+        assert_eq!(board.radlci(1), 1);
+
+        let mut round_two = HashMap::new();
+        round_two.insert(1, 30);
+        round_two.insert(2, 30);
+        board.add_round(&round_two);
+        assert_eq!(board.total(1), 140);
+        assert_eq!(board.total(2), 30);
+        assert_eq!(board.radlci(1), 0);
+
+        board.add_round(&round_two);
+        assert_eq!(board.total(1), 170);
+    }
+
+    #[test]
+    fn schneider_penalty_is_added_when_the_losing_side_is_below_threshold() {
+        let mut players = Players::new(4);
+        players.player_mut(3).set_partner(2);
+        init_cards(&mut players);
+        let cp = players.play_contract(3, Standard(Two));
+        let mut rules = NormalRules::new();
+        rules.schneider_penalty = 10;
+        rules.schneider_threshold = Some(20);
+        let scores = score_with_rules(&cp, &rules);
+        assert_eq!(scores[3], -45);
+        rules.schneider_threshold = Some(10);
+        let scores = score_with_rules(&cp, &rules);
+        assert_eq!(scores[3], -35);
+    }
+
+    #[test]
+    fn schneider_is_below_the_threshold() {
+        assert!(is_schneider(9, 10));
+        assert!(!is_schneider(10, 10));
+    }
+
+    #[test]
+    fn points_left_in_the_talon_belong_to_the_opponents() {
+        let mut players = Players::new(4);
+        players.player_mut(3).set_partner(2);
+        init_cards(&mut players);
+        let cp = players.play_contract(3, Standard(Two));
+        let rules = NormalRules::new();
+        let scores = score_with_talon(&cp, &rules, []);
+        assert_eq!(scores, score(&cp));
+        assert_eq!(scores[3], -35);
+        let scores = score_with_talon(&cp, &rules, [CARD_CLUBS_KING, CARD_DIAMONDS_QUEEN]);
+        assert_eq!(scores[3], -35);
+        assert_eq!(scores[2], -35);
+    }
+
+    #[test]
+    fn settled_scores_total_zero() {
+        // A three-player game where the declarer plays alone.
+        let mut players = Players::new(3);
+        init_half_points(&mut players, 1);
+        players.player_mut(0).pile_mut().add_card(CARD_DIAMONDS_KING);
+        players.player_mut(2).pile_mut().add_card(CARD_SPADES_KING);
+        let cp = players.play_contract(1, Standard(Three));
+        let scores = score(&cp);
+        assert_eq!(scores.len(), 1);
+        assert!(total(&scores) != 0);
+        let settled = settle_three_player(&scores, 1);
+        assert_eq!(settled.len(), 3);
+        assert_eq!(settled[1], scores[1]);
+        assert_eq!(total(&settled), 0);
+    }
+
+    #[test]
+    fn final_score_combines_contract_contra_and_bonuses() {
+        assert_eq!(final_score(&Solo(Two), true, 2, [Unannounced(Trula)]), 110);
+        assert_eq!(final_score(&Solo(Two), false, 2, [Unannounced(Trula)]), -90);
+    }
+
+    #[test]
+    fn scores_are_rounded_to_five() {
+        assert_eq!(round_to_five(32), 30);
+        assert_eq!(round_to_five(33), 35);
+        assert_eq!(round_to_five(-32), -30);
+        assert_eq!(round_to_five(-33), -35);
+        assert_eq!(round_to_five(0), 0);
+    }
 }
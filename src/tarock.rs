@@ -5,6 +5,8 @@
 #![feature(macro_rules)]
 #![feature(slicing_syntax)]
 
+extern crate serialize;
+
 #[cfg(test)]
 #[phase(plugin)]
 extern crate quickcheck_macros;
@@ -23,3 +25,4 @@ pub mod bonuses;
 pub mod announcements;
 pub mod game;
 pub mod scoring;
+pub mod replay;